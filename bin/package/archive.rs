@@ -0,0 +1,51 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Writes `from_dir`, the install directory of an assimp build, as prebuilt package to
+/// `archive_path`, in the layout `link_from_package()` in `build.rs` expects: `include/`, `lib/`,
+/// `bin/` when `with_bin` is set, and the license.
+///
+/// The archive is zstd compressed when `archive_path` ends in `.tar.zst`, gzipped otherwise.
+pub fn write_package(
+    from_dir: &Path,
+    license: &Path,
+    archive_path: &Path,
+    with_bin: bool,
+) -> io::Result<()> {
+    let tar_file = File::create(archive_path)?;
+
+    if archive_path.to_string_lossy().ends_with(".tar.zst") {
+        // The encoder compresses from a reader, so the tarball is assembled in memory first.
+        let tar = append_layout(tar::Builder::new(Vec::new()), from_dir, license, with_bin)?;
+        ruzstd::encoding::compress(
+            tar.as_slice(),
+            tar_file,
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+    } else {
+        let encoder = GzEncoder::new(tar_file, Compression::best());
+        append_layout(tar::Builder::new(encoder), from_dir, license, with_bin)?.finish()?;
+    }
+
+    Ok(())
+}
+
+fn append_layout<W: Write>(
+    mut archive: tar::Builder<W>,
+    from_dir: &Path,
+    license: &Path,
+    with_bin: bool,
+) -> io::Result<W> {
+    if with_bin {
+        archive.append_dir_all("bin", from_dir.join("bin"))?;
+    }
+
+    archive.append_dir_all("include", from_dir.join("include"))?;
+    archive.append_dir_all("lib", from_dir.join("lib"))?;
+    archive.append_file("LICENSE", &mut File::open(license)?)?;
+
+    archive.into_inner()
+}
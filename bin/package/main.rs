@@ -1,62 +1,76 @@
+mod archive;
+
 use russimp_sys::*;
 
-use flate2::write::GzEncoder;
-use flate2::Compression;
+use sha2::{Digest, Sha256};
 use std::fs::{self, File};
-use std::io;
+use std::io::{self, Write};
 use std::path::PathBuf;
 
-const LICENSE_FILEPATH: &str = "LICENSE";
-
 const fn static_lib() -> &'static str {
-    if cfg!(feature = "build-assimp") && cfg!(not(feature = "static-link")) {
-        "dylib"
-    } else if cfg!(feature = "static-link") {
+    if cfg!(feature = "static-link") {
         "static"
     } else {
-        ""
+        "dylib"
     }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    if static_lib().is_empty() {
-        return Err(Box::new(io::Error::new(
-            io::ErrorKind::Other,
-            "You must specify either the `static-link` or `build-assimp` feature",
-        )));
-    }
-
     let out_dir = PathBuf::from(env!("OUT_DIR"));
     let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    let ar_dst_dir = PathBuf::from(option_env!("RUSSIMP_PACKAGE_DIR").unwrap_or(env!("OUT_DIR")));
+    let ar_dst_dir = option_env!("RUSSIMP_PACKAGE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| manifest_dir.join("target").join("russimp-package"));
+
+    if russimp_sys::built_info::ASSIMP_BUILD_PROFILE != "Release" {
+        return Err(format!(
+            "assimp was built in the {} configuration, prebuilt packages are built in Release",
+            russimp_sys::built_info::ASSIMP_BUILD_PROFILE
+        )
+        .into());
+    }
 
-    let target = russimp_sys::built_info::TARGET;
+    // `universal-apple-darwin` for builds with `RUSSIMP_MACOS_ARCHITECTURES=arm64;x86_64`.
+    let target = russimp_sys::built_info::PREBUILT_PACKAGE_TARGET;
+    // `--zstd` creates the faster to extract `.tar.zst` archive preferred by the build script.
+    let extension = if std::env::args().any(|arg| arg == "--zstd") {
+        "tar.zst"
+    } else {
+        "tar.gz"
+    };
     let ar_filename = format!(
-        "russimp-{}-{}-{}.tar.gz",
-        env!("CARGO_PKG_VERSION"),
+        "russimp-{}-{}-{}.{}",
+        russimp_sys::built_info::PREBUILT_PACKAGE_VERSION,
         target,
-        static_lib()
+        // On MSVC with the C runtime, e.g. `static-md`.
+        russimp_sys::built_info::PREBUILT_PACKAGE_LINKAGE,
+        extension
     );
 
     let from_dir = out_dir.join(static_lib());
-    let mut licence = File::open(manifest_dir.join(LICENSE_FILEPATH))?;
 
     fs::create_dir_all(&ar_dst_dir)?;
     println!("Packaging at: {}", ar_dst_dir.display());
 
-    let tar_file = File::create(ar_dst_dir.join(&ar_filename))?;
-    let mut archive = tar::Builder::new(GzEncoder::new(tar_file, Compression::best()));
-
     // On Windows, the dynamic libraries are located in the bin directory.
-    if static_lib() == "dylib" && cfg!(target_env = "msvc") {
-        archive.append_dir_all(format!("bin"), from_dir.join("bin"))?;
-    }
-
-    archive.append_dir_all("include", from_dir.join("include"))?;
-    archive.append_dir_all(format!("lib"), from_dir.join("lib"))?;
-    archive.append_file(format!("{}", LICENSE_FILEPATH), &mut licence)?;
+    archive::write_package(
+        &from_dir,
+        // Copied there by the build script from the assimp source.
+        &out_dir.join("LICENSE"),
+        &ar_dst_dir.join(&ar_filename),
+        static_lib() == "dylib" && cfg!(target_os = "windows"),
+    )?;
 
-    archive.finish()?;
+    // Published next to the archive, and collected into the `prebuilt.sha256` of the release.
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(ar_dst_dir.join(&ar_filename))?, &mut hasher)?;
+    let sha256: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let mut sha256_file = File::create(ar_dst_dir.join(format!("{}.sha256", ar_filename)))?;
+    writeln!(sha256_file, "{}  {}", sha256, ar_filename)?;
 
     let (major, minor, patch) = unsafe {
         (
@@ -67,8 +81,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     println!(
-        "Package created at: {}\nAssimp version: {}.{}.{}",
+        "Package created at: {}\nSHA-256: {}\nAssimp version: {}.{}.{}",
         ar_dst_dir.join(&ar_filename).display(),
+        sha256,
         major,
         minor,
         patch,
@@ -0,0 +1,1593 @@
+//! Building assimp from source with CMake, and the build cache of installed builds.
+
+use super::*;
+
+// Compiler specific compiler flags for CMake
+pub(crate) fn compiler_flags(target: &TargetInfo) -> Vec<&'static str> {
+    let mut flags = Vec::new();
+
+    if target.is_msvc() {
+        flags.push("/EHsc");
+    }
+
+    flags
+}
+
+/// Whether assimp is built for size, by the `min-size` feature or `RUSSIMP_MIN_SIZE=1`.
+pub(crate) fn min_size() -> bool {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_MIN_SIZE");
+    cfg!(feature = "min-size") || env::var("RUSSIMP_MIN_SIZE").as_deref() == Ok("1")
+}
+
+/// The compiler flags of `min_size` on top of the `-Os` or `/O1` of MinSizeRel: every function and
+/// variable in a section of its own, which the linker drops when it is unused.
+pub(crate) fn size_flags(target: &TargetInfo) -> &'static [&'static str] {
+    if target.is_msvc() {
+        &["/Gy", "/Gw"]
+    } else {
+        &["-ffunction-sections", "-fdata-sections"]
+    }
+}
+
+/// The linker flags dropping the unused sections of `size_flags`, which rustc passes when linking
+/// its binaries; a shared assimp is linked with them in MinSizeRel.
+pub(crate) fn section_gc_flags(target: &TargetInfo) -> &'static str {
+    if target.is_msvc() {
+        "/INCREMENTAL:NO /OPT:REF /OPT:ICF"
+    } else if target.os == "macos" || target.is_apple_mobile() {
+        "-Wl,-dead_strip"
+    } else {
+        "-Wl,--gc-sections"
+    }
+}
+
+/// The sanitizers `RUSSIMP_SANITIZER` can instrument assimp with.
+const SANITIZERS: &[&str] = &["address", "undefined", "thread"];
+
+/// The sanitizer to instrument assimp with, from `RUSSIMP_SANITIZER`.
+pub(crate) fn assimp_sanitizer() -> Result<Option<&'static str>, BuildError> {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_SANITIZER");
+    let sanitizer = env::var("RUSSIMP_SANITIZER").unwrap_or_default();
+    if sanitizer.trim().is_empty() {
+        return Ok(None);
+    }
+    SANITIZERS
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(sanitizer.trim()))
+        .map(|known| Some(*known))
+        .ok_or_else(|| BuildError::Validation {
+            reason: format!(
+                "Unknown RUSSIMP_SANITIZER {:?}, expected one of {}.",
+                sanitizer,
+                SANITIZERS.join(", ")
+            ),
+            hint: "Set RUSSIMP_SANITIZER to one of them, or unset it.".to_string(),
+        })
+}
+
+/// The compiler flags instrumenting assimp with `sanitizer`. MSVC only has AddressSanitizer, and
+/// its runtime for the DLL C runtime the Rust code links against.
+pub(crate) fn sanitizer_flags(
+    target: &TargetInfo,
+    sanitizer: &str,
+    static_crt: bool,
+) -> Result<&'static [&'static str], String> {
+    match sanitizer {
+        _ if target.is_msvc() && sanitizer != "address" => Err(format!(
+            "MSVC has no {} sanitizer, only RUSSIMP_SANITIZER=address",
+            sanitizer
+        )),
+        _ if target.is_msvc() && static_crt => Err(
+            "AddressSanitizer builds of assimp need the DLL C runtime, set RUSSIMP_STATIC_CRT=0"
+                .to_string(),
+        ),
+        _ if target.is_msvc() => Ok(&["/fsanitize=address"]),
+        "address" => Ok(&["-fsanitize=address", "-fno-omit-frame-pointer"]),
+        "undefined" => Ok(&["-fsanitize=undefined"]),
+        _ => Ok(&["-fsanitize=thread"]),
+    }
+}
+
+/// The RUSTFLAGS the Rust code has to be built with to link the runtime of `sanitizer` the
+/// instrumented assimp needs, and how to build it. rustc has no UndefinedBehaviorSanitizer, its
+/// runtime is linked by the compiler driver.
+pub(crate) fn sanitizer_rustflags(target: &TargetInfo, sanitizer: &str) -> (String, String) {
+    match sanitizer {
+        "undefined" => (
+            "-C link-arg=-fsanitize=undefined".to_string(),
+            "cargo build".to_string(),
+        ),
+        _ => (
+            format!("-Zsanitizer={}", sanitizer),
+            format!(
+                "cargo +nightly build -Zbuild-std --target {}",
+                target.triple
+            ),
+        ),
+    }
+}
+
+/// The families of compilers the link time optimization of the `lto` feature differs between.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CompilerFamily {
+    Msvc,
+    Clang,
+    Gnu,
+}
+
+impl CompilerFamily {
+    fn of(compiler: &cc::Tool) -> Self {
+        if compiler.is_like_clang() || compiler.is_like_clang_cl() {
+            CompilerFamily::Clang
+        } else if compiler.is_like_msvc() {
+            CompilerFamily::Msvc
+        } else {
+            CompilerFamily::Gnu
+        }
+    }
+}
+
+/// How assimp is built with the `lto` feature.
+#[derive(Debug, PartialEq)]
+pub(crate) enum AssimpLto {
+    /// The interprocedural optimization of CMake: LTO finishes when linking the shared assimp, or
+    /// with the `/LTCG` link.exe restarts with for the `/GL` objects of a static MSVC assimp.
+    Ipo,
+    /// Compiler flags for the objects of a static assimp, which link in a Rust build without LTO.
+    Flags(&'static [&'static str]),
+    /// No LTO, and why.
+    Off(&'static str),
+}
+
+/// The LTO assimp is built with by `compiler`. The objects of a static assimp have to link without
+/// LTO as well: GCC keeps their machine code next to the LTO bytecode, while the bitcode of clang
+/// only links with the `-Clinker-plugin-lto` of rustc. `RUSSIMP_LTO=0` turns it off.
+pub(crate) fn assimp_lto(
+    compiler: CompilerFamily,
+    static_link: bool,
+    linker_plugin_lto: bool,
+    disabled: bool,
+) -> AssimpLto {
+    match compiler {
+        _ if disabled => AssimpLto::Off("RUSSIMP_LTO=0"),
+        _ if !static_link => AssimpLto::Ipo,
+        CompilerFamily::Msvc => AssimpLto::Ipo,
+        CompilerFamily::Gnu => AssimpLto::Flags(&["-flto=auto", "-ffat-lto-objects"]),
+        CompilerFamily::Clang if linker_plugin_lto => AssimpLto::Flags(&["-flto=thin"]),
+        CompilerFamily::Clang => AssimpLto::Off(
+            "the LLVM bitcode of clang only links with `-Clinker-plugin-lto` in the RUSTFLAGS",
+        ),
+    }
+}
+
+/// Whether rustc runs the cross-language LTO of `-Clinker-plugin-lto`.
+fn linker_plugin_lto() -> bool {
+    env::var("CARGO_ENCODED_RUSTFLAGS")
+        .map(|flags| {
+            flags
+                .split('\x1f')
+                .any(|flag| flag.contains("linker-plugin-lto"))
+        })
+        .unwrap_or(false)
+}
+
+/// Splits `value` into words like a POSIX shell does: at whitespace outside of single and double
+/// quotes, with backslashes escaping the next character outside of quotes and `"` or `\` inside
+/// double quotes.
+pub(crate) fn split_shell_words(value: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                word.extend(chars.by_ref().take_while(|&c| c != '\''));
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            let mut rest = chars.clone();
+                            match rest.next() {
+                                Some(escaped @ ('"' | '\\')) => {
+                                    word.push(escaped);
+                                    chars = rest;
+                                }
+                                _ => word.push('\\'),
+                            }
+                        }
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => word.get_or_insert_with(String::new).extend(chars.next()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+/// The compiler variable `var` for `target`, e.g. `CXXFLAGS`, looked up like the cc crate does.
+fn target_compiler_var(target: &TargetInfo, var: &str) -> Option<String> {
+    let vars = [
+        format!("{}_{}", var, target.triple),
+        format!("{}_{}", var, target.triple.replace('-', "_")),
+        format!("TARGET_{}", var),
+        var.to_string(),
+    ];
+    for var in &vars {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    vars.iter().find_map(|var| env::var(var).ok())
+}
+
+/// The extra compiler flags in `var`, e.g. `RUSSIMP_CFLAGS`, split like a shell would.
+fn extra_compiler_flags(var: &str) -> Vec<String> {
+    println!("cargo:rerun-if-env-changed={}", var);
+    env::var(var)
+        .map(|flags| split_shell_words(&flags))
+        .unwrap_or_default()
+}
+
+/// Quotes `flag` for the command lines CMake inserts its flag variables into, if it has spaces.
+pub(crate) fn cmake_flag(flag: &str) -> String {
+    if flag.contains(char::is_whitespace) {
+        format!("\"{}\"", flag.replace('"', "\\\""))
+    } else {
+        flag.to_string()
+    }
+}
+
+/// Parses the `KEY=VALUE` pairs of `RUSSIMP_CMAKE_DEFINES`, separated by semicolons. The key may
+/// carry a type like `ASSIMP_ASAN:BOOL`, the value may be empty.
+pub(crate) fn parse_cmake_defines(list: &str) -> Result<Vec<(String, String)>, String> {
+    list.split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() && !key.trim().contains(' ') => {
+                Ok((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => Err(format!(
+                "RUSSIMP_CMAKE_DEFINES entry {:?} is not a KEY=VALUE pair",
+                entry
+            )),
+        })
+        .collect()
+}
+
+/// The extra CMake definitions of `RUSSIMP_CMAKE_DEFINES`, passed after the ones of the build
+/// script so they take precedence.
+fn user_cmake_defines() -> Result<Vec<(String, String)>, BuildError> {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_CMAKE_DEFINES");
+    parse_cmake_defines(&env::var("RUSSIMP_CMAKE_DEFINES").unwrap_or_default()).map_err(|e| {
+        BuildError::Validation {
+            reason: format!("{}.", e),
+            hint: "Separate the KEY=VALUE definitions of RUSSIMP_CMAKE_DEFINES with semicolons, \
+                e.g. `ASSIMP_BUILD_ZLIB=ON;ASSIMP_ASAN:BOOL=ON`."
+                .to_string(),
+        }
+    })
+}
+
+/// The definitions of the build script the linking of assimp relies on, and what overriding them
+/// breaks.
+const REQUIRED_CMAKE_DEFINES: &[(&str, &str)] = &[
+    (
+        "BUILD_SHARED_LIBS",
+        "the library is linked as the `static-link` feature selects",
+    ),
+    (
+        "ASSIMP_BUILD_ZLIB",
+        "the zlib linked is the one the zlib features select",
+    ),
+    (
+        "ASSIMP_DOUBLE_PRECISION",
+        "the bindings use the `ai_real` of the `double-precision` feature",
+    ),
+    ("LIBRARY_SUFFIX", "the library is linked without a suffix"),
+    (
+        "USE_STATIC_CRT",
+        "the C runtime has to match the one of the Rust code",
+    ),
+];
+
+/// Warnings about the `user` definitions overriding those of `defines` the build relies on.
+pub(crate) fn required_define_overrides(
+    defines: &[(&str, OsString)],
+    user: &[(String, String)],
+) -> Vec<String> {
+    user.iter()
+        .filter_map(|(key, value)| {
+            let name = key.split(':').next().unwrap();
+            let (_, reason) = REQUIRED_CMAKE_DEFINES
+                .iter()
+                .find(|(required, _)| *required == name)?;
+            let (_, ours) = defines.iter().find(|(ours, _)| *ours == name)?;
+            (ours.to_str() != Some(value.as_str())).then(|| {
+                format!(
+                    "RUSSIMP_CMAKE_DEFINES overrides {}={} with {}, but {}: the build may fail to \
+                    link or crash",
+                    name,
+                    ours.to_string_lossy(),
+                    value,
+                    reason
+                )
+            })
+        })
+        .collect()
+}
+
+/// The CMake generator to build with: Ninja when `ninja` is installed, the Makefiles of MinGW for
+/// the gnu ABI on a Windows host otherwise, e.g. in MSYS2, where the Visual Studio generator CMake
+/// defaults to would pick MSVC. `None` leaves it to CMake.
+pub(crate) fn cmake_generator(
+    target: &TargetInfo,
+    ninja: bool,
+    windows_host: bool,
+) -> Option<&'static str> {
+    if ninja {
+        Some("Ninja")
+    } else if !target.is_msvc() && windows_host {
+        Some("MinGW Makefiles")
+    } else {
+        None
+    }
+}
+
+/// The generator the assimp build is configured with: the one `forced` by
+/// `RUSSIMP_CMAKE_GENERATOR`, none to leave it to CMake when `CMAKE_GENERATOR` is set, the one of
+/// `cmake_generator` otherwise. It is passed to the cmake crate rather than set in the environment
+/// of the build script.
+pub(crate) fn configured_generator(
+    target: &TargetInfo,
+    forced: Option<&str>,
+    cmake_generator_set: bool,
+    ninja: bool,
+    windows_host: bool,
+) -> Option<String> {
+    match forced {
+        Some(generator) => Some(generator.to_string()),
+        None if cmake_generator_set => None,
+        None => cmake_generator(target, ninja, windows_host).map(str::to_string),
+    }
+}
+
+/// The generator CMake builds with: the one selected by the build script or `CMAKE_GENERATOR`, else
+/// CMake's default, Visual Studio for MSVC and Makefiles elsewhere.
+pub(crate) fn effective_generator(target: &TargetInfo, generator: Option<&str>) -> String {
+    generator
+        .map(str::to_string)
+        .or_else(|| env::var("CMAKE_GENERATOR").ok())
+        .unwrap_or_else(|| {
+            if target.is_msvc() {
+                "Visual Studio".to_string()
+            } else {
+                "Unix Makefiles".to_string()
+            }
+        })
+}
+
+/// The number of parallel jobs to compile assimp with, and where it comes from:
+/// `RUSSIMP_BUILD_JOBS`, else the `NUM_JOBS` cargo sets from its `-j`.
+fn assimp_build_jobs() -> Result<Option<(u32, &'static str)>, BuildError> {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_BUILD_JOBS");
+    if let Ok(jobs) = env::var("RUSSIMP_BUILD_JOBS") {
+        return match jobs.trim().parse() {
+            Ok(jobs) if jobs > 0 => Ok(Some((jobs, "RUSSIMP_BUILD_JOBS"))),
+            _ => Err(BuildError::Validation {
+                reason: format!(
+                    "RUSSIMP_BUILD_JOBS={:?} is not a positive number of jobs.",
+                    jobs
+                ),
+                hint:
+                    "Set RUSSIMP_BUILD_JOBS to a number above 0, or unset it to run as many jobs \
+                    as cargo does."
+                        .to_string(),
+            }),
+        };
+    }
+    Ok(env::var("NUM_JOBS")
+        .ok()
+        .and_then(|jobs| jobs.parse().ok())
+        .map(|jobs| (jobs, "NUM_JOBS")))
+}
+
+/// The arguments passed to the native build tool of `generator` to run `jobs` jobs in parallel.
+/// They come after the `--parallel` of the cmake crate, and the last one wins.
+pub(crate) fn parallel_build_args(generator: &str, jobs: u32) -> Vec<String> {
+    if generator.starts_with("Ninja") || generator.contains("Makefiles") {
+        vec![format!("-j{}", jobs)]
+    } else if generator.starts_with("Visual Studio") {
+        vec![format!("/m:{}", jobs)]
+    } else if generator == "Xcode" {
+        vec!["-jobs".to_string(), jobs.to_string()]
+    } else {
+        Vec::new()
+    }
+}
+
+/// The compiler launcher to compile assimp through, and where it comes from:
+/// `RUSSIMP_COMPILER_LAUNCHER`, else the sccache of `RUSTC_WRAPPER` with
+/// `RUSSIMP_USE_RUSTC_WRAPPER=1`.
+fn compiler_launcher() -> Option<(String, &'static str)> {
+    for var in [
+        "RUSSIMP_COMPILER_LAUNCHER",
+        "RUSTC_WRAPPER",
+        "RUSSIMP_USE_RUSTC_WRAPPER",
+    ] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    launcher_from(
+        env::var("RUSSIMP_COMPILER_LAUNCHER").ok(),
+        env::var("RUSTC_WRAPPER").ok(),
+        env::var("RUSSIMP_USE_RUSTC_WRAPPER").as_deref() == Ok("1"),
+    )
+}
+
+/// The launcher of `compiler_launcher` from the values of its variables. Only sccache can launch
+/// compilers as well as rustc, other wrappers are left to rustc. The wrapper may be a path of
+/// either host.
+pub(crate) fn launcher_from(
+    launcher: Option<String>,
+    rustc_wrapper: Option<String>,
+    use_rustc_wrapper: bool,
+) -> Option<(String, &'static str)> {
+    if let Some(launcher) = launcher.filter(|launcher| !launcher.trim().is_empty()) {
+        return Some((launcher.trim().to_string(), "RUSSIMP_COMPILER_LAUNCHER"));
+    }
+    rustc_wrapper
+        .filter(|wrapper| {
+            use_rustc_wrapper
+                && wrapper
+                    .trim()
+                    .rsplit(['/', '\\'])
+                    .next()
+                    .map(|name| name.trim_end_matches(".exe"))
+                    .is_some_and(|name| name.eq_ignore_ascii_case("sccache"))
+        })
+        .map(|wrapper| (wrapper.trim().to_string(), "RUSTC_WRAPPER"))
+}
+
+/// A CMake script included by every `project()` of the build that compiles the debug info into the
+/// objects with `/Z7`. sccache and ccache can't cache the PDB `/Zi` writes, which assimp adds to
+/// its Debug and Release flags; the `/Z7` comes after them and the last one wins.
+const EMBEDDED_DEBUG_INFO: &str = "# Written by the build script of russimp-sys.
+if(MSVC)
+  add_compile_options($<$<COMPILE_LANGUAGE:C,CXX>:/Z7>)
+endif()
+";
+
+/// Whether assimp and the zlib it builds are compiled as position independent code: always on musl,
+/// where Rust links static position independent executables, and for the static libraries of the
+/// other targets but Windows and the web, so they link into `cdylib`s as well. `RUSSIMP_PIC`
+/// overrides the default.
+pub(crate) fn position_independent_code(
+    target: &TargetInfo,
+    static_link: bool,
+    setting: Option<&str>,
+) -> Result<bool, String> {
+    match setting.map(str::trim) {
+        _ if target.env == "musl" => Ok(true),
+        None | Some("") => Ok(static_link && !target.is_windows() && !target.is_emscripten()),
+        Some("1" | "on" | "ON") => Ok(true),
+        Some("0" | "off" | "OFF") => Ok(false),
+        Some(setting) => Err(format!("RUSSIMP_PIC={:?} is neither 1 nor 0", setting)),
+    }
+}
+
+/// The directory of the static `libstdc++.a` of the C++ compiler `cxx`, which rustc has to find to
+/// link it statically.
+fn static_cxx_runtime_dir(cxx: &cc::Tool) -> Option<PathBuf> {
+    let output = cxx
+        .to_command()
+        .arg("-print-file-name=libstdc++.a")
+        .output()
+        .ok()?;
+    // Without one, the compiler prints the file name as is.
+    let library = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    if !library.is_absolute() || !library.is_file() {
+        return None;
+    }
+    library.parent().map(Path::to_path_buf)
+}
+
+/// The CMake variables describing a cross-compilation setup that are forwarded from the
+/// environment of the same name to the assimp build.
+const CMAKE_CROSS_VARS: &[&str] = &[
+    "CMAKE_SYSTEM_NAME",
+    "CMAKE_SYSTEM_PROCESSOR",
+    "CMAKE_SYSROOT",
+];
+
+/// The CMake toolchain file selected for the assimp build: `RUSSIMP_CMAKE_TOOLCHAIN_FILE`, or the
+/// `CMAKE_TOOLCHAIN_FILE_<target>`, `TARGET_CMAKE_TOOLCHAIN_FILE` and `CMAKE_TOOLCHAIN_FILE` the
+/// cmake crate reads as well.
+fn cmake_toolchain_file(target: &TargetInfo) -> Option<PathBuf> {
+    let vars = [
+        "RUSSIMP_CMAKE_TOOLCHAIN_FILE".to_string(),
+        format!("CMAKE_TOOLCHAIN_FILE_{}", target.triple),
+        format!("CMAKE_TOOLCHAIN_FILE_{}", target.triple.replace('-', "_")),
+        "TARGET_CMAKE_TOOLCHAIN_FILE".to_string(),
+        "CMAKE_TOOLCHAIN_FILE".to_string(),
+    ];
+    for var in &vars {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    vars.iter()
+        .find_map(|var| env::var_os(var).filter(|file| !file.is_empty()))
+        .map(PathBuf::from)
+}
+
+pub(crate) fn build_from_source(
+    target: &TargetInfo,
+    assimp_version: &str,
+    assimp_src_dir: &Path,
+    system_zlib: Option<&SystemZlib>,
+) -> Result<(), BuildError> {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let static_build = static_lib(target) == "static";
+
+    let build_zlib = build_zlib(target)?;
+    let mut defines = assimp_options(build_zlib, static_build, system_zlib);
+    let components = component_selection(build_zlib)?;
+    for (key, value) in &components.defines {
+        defines.push((key, value.into()));
+    }
+    let user_defines = user_cmake_defines()?;
+
+    // A toolchain file of the user's own sets up the compilers and the platform, which the
+    // defaults for the target below would conflict with.
+    let toolchain_file = cmake_toolchain_file(target);
+    let platform_setup = toolchain_file.is_none();
+    if let Some(toolchain_file) = &toolchain_file {
+        defines.push(("CMAKE_TOOLCHAIN_FILE", toolchain_file.into()));
+    }
+    for var in CMAKE_CROSS_VARS {
+        println!("cargo:rerun-if-env-changed={}", var);
+        if let Some(value) = env::var_os(var).filter(|value| !value.is_empty()) {
+            defines.push((var, value));
+        }
+    }
+
+    // CMake
+    let profile = assimp_build_profile()?;
+    let install_dir = assimp_install_dir(&out_dir, target)?;
+    let mut cmake = cmake::Config::new(assimp_src_dir);
+    cmake.profile(profile).out_dir(&install_dir);
+    info!("building assimp in the {} configuration", profile);
+
+    let sanitizer = assimp_sanitizer()?;
+    let flags = assimp_flags(target, static_build, sanitizer, &mut defines)?;
+    for flag in &flags.cflags {
+        cmake.cflag(cmake_flag(flag));
+    }
+    for flag in &flags.cxxflags {
+        cmake.cxxflag(cmake_flag(flag));
+    }
+
+    install_defines(target, static_build, &mut cmake, &mut defines);
+    let generator = assimp_generator(target, platform_setup, &mut cmake, &mut defines);
+    let build_generator = effective_generator(target, generator.as_deref());
+    assimp_build_parallelism(&build_generator, &mut cmake)?;
+
+    // The CMake cache keeps the components of an earlier configuration enabled, and CMake refuses
+    // to switch the generator of a build directory: it is configured from scratch when either, or
+    // `slim`, the sanitizer or the assimp source, changed.
+    reset_stale_configuration(
+        &install_dir,
+        &format!(
+            "{}\n{}\n{}\n{}\n{:?}\n{}\n{}",
+            assimp_version,
+            assimp_src_dir.display(),
+            fs::read_to_string(source_manifest_path(&out_dir)).unwrap_or_default(),
+            components.summary,
+            user_defines,
+            build_generator,
+            sanitizer.unwrap_or("")
+        ),
+    )?;
+
+    launcher_defines(target, &out_dir, &mut defines)?;
+    platform_defines(target, static_build, platform_setup, &mut defines)?;
+    flag_defines(target, &flags, &mut defines)?;
+
+    for (key, value) in &defines {
+        cmake.define(key, value);
+    }
+    // The last definition of a variable on the command line of CMake wins.
+    for warning in required_define_overrides(&defines, &user_defines) {
+        warning!("{}", warning);
+    }
+    for (key, value) in &user_defines {
+        cmake.define(key, value);
+    }
+    info!(
+        "configuring assimp with {}{}",
+        generator
+            .map(|generator| format!("-G \"{}\" ", generator))
+            .unwrap_or_default(),
+        defines
+            .iter()
+            .filter(|(key, _)| {
+                !user_defines
+                    .iter()
+                    .any(|(user, _)| user.split(':').next() == Some(*key))
+            })
+            .map(|(key, value)| format!("-D{}={}", key, value.to_string_lossy()))
+            .chain(
+                user_defines
+                    .iter()
+                    .map(|(key, value)| format!("-D{}={}", key, value))
+            )
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+
+    let wrapper = fs::read_to_string("wrapper.h").map_err(|e| BuildError::Validation {
+        reason: format!("Unable to read wrapper.h: {}.", e),
+        hint: "Check that the package of russimp-sys is complete.".to_string(),
+    })?;
+    // Everything the libraries depend on, as far as the build script knows it.
+    let (cc, cxx) = target_compilers(target)?;
+    let configuration: Vec<String> = defines
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value.to_string_lossy()))
+        .chain(
+            user_defines
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value)),
+        )
+        .chain(flags.cflags.iter().chain(&flags.cxxflags).cloned())
+        .chain(
+            cc.args()
+                .iter()
+                .chain(cxx.args())
+                .map(|arg| arg.to_string_lossy().into_owned()),
+        )
+        .chain(std::iter::once(profile.to_string()))
+        .collect();
+    let cache_entry = build_cache_dir().and_then(|cache_dir| {
+        let key = build_cache_key(
+            &cached_source_sha256(assimp_version)?,
+            &target.triple,
+            &[compiler_identity(&cc), compiler_identity(&cxx)],
+            &configuration,
+        );
+        Some(cache_dir.join(key))
+    });
+
+    let cmake_dir =
+        if let Some(entry) = cached_build(cache_entry.as_deref(), &wrapper, &install_dir) {
+            info!("using the assimp built before in {}", entry.display());
+            install_dir
+        } else {
+            install_assimp(&mut cmake, assimp_src_dir, &wrapper, cache_entry.as_deref())?
+        };
+    for (file, size) in assimp_library_sizes(&cmake_dir) {
+        info!(
+            "built {} ({:.1} MiB)",
+            file.display(),
+            size as f64 / (1024.0 * 1024.0)
+        );
+    }
+
+    emit_link_search(&cmake_dir.join("lib"));
+
+    emit_link_search(&cmake_dir.join("bin"));
+    Ok(())
+}
+
+/// The options of assimp that follow the features, and the zlib it is built against. They win over
+/// whatever a toolchain file sets: they are passed as cache entries, which it doesn't override.
+fn assimp_options(
+    build_zlib: bool,
+    static_build: bool,
+    system_zlib: Option<&SystemZlib>,
+) -> Vec<(&'static str, OsString)> {
+    let on = |enabled: bool| OsString::from(if enabled { "ON" } else { "OFF" });
+    let mut defines: Vec<(&str, OsString)> = vec![
+        ("BUILD_SHARED_LIBS", on(!static_build)),
+        // The command-line tool, but never the viewer, which needs DirectX on Windows.
+        ("ASSIMP_BUILD_ASSIMP_TOOLS", on(build_tools())),
+        ("ASSIMP_BUILD_ASSIMP_VIEW", on(false)),
+        ("ASSIMP_BUILD_TESTS", on(false)),
+        ("ASSIMP_BUILD_ZLIB", on(build_zlib)),
+        // Disable being overly strict with warnings, which can cause build issues
+        // such as: https://github.com/assimp/assimp/issues/5315
+        ("ASSIMP_WARNINGS_AS_ERRORS", on(false)),
+        ("LIBRARY_SUFFIX", "".into()),
+        // Leaves out the exporters, the `aiExport*` functions are still declared in the headers.
+        ("ASSIMP_NO_EXPORT", on(cfg!(feature = "no-export"))),
+        // Decodes the KHR_draco_mesh_compression meshes of glTF files, which are empty otherwise.
+        ("ASSIMP_BUILD_DRACO", on(cfg!(feature = "draco"))),
+        ("ASSIMP_BUILD_DRACO_STATIC", on(cfg!(feature = "draco"))),
+        (
+            "ASSIMP_DOUBLE_PRECISION",
+            on(cfg!(feature = "double-precision")),
+        ),
+    ];
+    if LIBZ_SYS {
+        let zlib_defines =
+            libz_sys_defines(env::var_os("DEP_Z_ROOT"), env::var_os("DEP_Z_INCLUDE"));
+        if cfg!(feature = "zlib-ng") {
+            info!(
+                "building assimp against the zlib-ng of libz-sys: {:?}",
+                zlib_defines
+            );
+        } else if zlib_defines.is_empty() {
+            info!("building assimp against the system zlib linked by libz-sys");
+        } else {
+            info!(
+                "building assimp against the zlib of libz-sys: {:?}",
+                zlib_defines
+            );
+        }
+        defines.extend(zlib_defines);
+    }
+
+    if let Some(zlib) = system_zlib {
+        info!("building assimp against the system zlib: {:?}", zlib);
+        defines.extend(zlib.defines());
+    }
+    defines
+}
+
+/// The importers, exporters and contrib libraries selected by the features.
+struct ComponentSelection {
+    /// The defines selecting them.
+    defines: Vec<(String, &'static str)>,
+    /// A summary of the selection, the CMake configuration is redone when it changes.
+    summary: String,
+}
+
+/// The components of the features, logging which ones are built.
+fn component_selection(build_zlib: bool) -> Result<ComponentSelection, BuildError> {
+    warn_unknown_importers();
+    let importers = selected_importers();
+    if !importers.is_empty() {
+        info!("building the {} importers only", importers.join(", "));
+    } else if slim() {
+        info!(
+            "building assimp without the {} importers",
+            SLIM_EXCLUDED_IMPORTERS
+                .iter()
+                .map(|(importer, _)| *importer)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    let exporters = EXPORTERS.enabled(feature_enabled);
+    if !exporters.is_empty() {
+        info!("building the {} exporters only", exporters.join(", "));
+    }
+    warn_unknown_contribs();
+    let contribs = disabled_contribs();
+    let contrib_names: Vec<&str> = contribs.iter().map(|contrib| contrib.name).collect();
+    if !contribs.is_empty() {
+        info!(
+            "leaving out the contrib libraries {}",
+            contrib_names.join(", ")
+        );
+    }
+    let defines = IMPORTERS
+        .defines(&importers)
+        .into_iter()
+        .chain(slim_defines(slim(), &importers))
+        .chain(EXPORTERS.defines(&exporters))
+        .chain(
+            contrib_defines(&contribs, &importers, &exporters, build_zlib)
+                .map_err(contrib_error)?,
+        )
+        .collect();
+    let summary = format!(
+        "{}\n{}\n{}\n{}",
+        importers.join(";"),
+        slim(),
+        exporters.join(";"),
+        contrib_names.join(";")
+    );
+    Ok(ComponentSelection { defines, summary })
+}
+
+/// The compiler flags assimp is built with: those of the environment, which the cmake crate passes
+/// itself, and the ones of the build script.
+struct AssimpFlags {
+    env_cflags: Option<String>,
+    env_cxxflags: Option<String>,
+    cflags: Vec<String>,
+    cxxflags: Vec<String>,
+}
+
+impl AssimpFlags {
+    /// Adds `flags` to both the C and the C++ flags.
+    fn extend(&mut self, flags: &[&str]) {
+        self.cflags
+            .extend(flags.iter().map(|flag| flag.to_string()));
+        self.cxxflags
+            .extend(flags.iter().map(|flag| flag.to_string()));
+    }
+}
+
+/// The flags of the LTO, the sanitizer and the size features, with the defines they need.
+fn assimp_flags(
+    target: &TargetInfo,
+    static_build: bool,
+    sanitizer: Option<&str>,
+    defines: &mut Vec<(&str, OsString)>,
+) -> Result<AssimpFlags, BuildError> {
+    // The compilers and flags of CC, CXX, CFLAGS and CXXFLAGS reach CMake through the cmake crate,
+    // the extra flags of RUSSIMP_CFLAGS and RUSSIMP_CXXFLAGS come after the built-in ones.
+    for var in ["CC", "CXX"] {
+        target_compiler_var(target, var);
+    }
+    let flags = |extra_var: &str| -> Vec<String> {
+        compiler_flags(target)
+            .into_iter()
+            .map(str::to_string)
+            .chain(extra_compiler_flags(extra_var))
+            .collect()
+    };
+    let mut assimp_flags = AssimpFlags {
+        env_cflags: target_compiler_var(target, "CFLAGS"),
+        env_cxxflags: target_compiler_var(target, "CXXFLAGS"),
+        cflags: flags("RUSSIMP_CFLAGS"),
+        cxxflags: flags("RUSSIMP_CXXFLAGS"),
+    };
+    if cfg!(feature = "lto") {
+        println!("cargo:rerun-if-env-changed=RUSSIMP_LTO");
+        let (_, cxx) = target_compilers(target)?;
+        let family = CompilerFamily::of(&cxx);
+        let lto = assimp_lto(
+            family,
+            static_build,
+            linker_plugin_lto(),
+            matches!(env::var("RUSSIMP_LTO").as_deref(), Ok("0" | "off")),
+        );
+        match lto {
+            AssimpLto::Ipo => {
+                info!(
+                    "building assimp with the interprocedural optimization of CMake ({:?})",
+                    family
+                );
+                defines.push(("CMAKE_INTERPROCEDURAL_OPTIMIZATION", "ON".into()));
+                if family == CompilerFamily::Msvc && static_build {
+                    info!(
+                        "the /GL objects of assimp are optimized at the link, link.exe restarts \
+                        with /LTCG for them"
+                    );
+                }
+            }
+            AssimpLto::Flags(lto_flags) => {
+                info!(
+                    "building assimp with {} ({:?})",
+                    lto_flags.join(" "),
+                    family
+                );
+                assimp_flags.extend(lto_flags);
+            }
+            AssimpLto::Off(reason) => {
+                warning!("building assimp without LTO: {}", reason)
+            }
+        }
+    }
+    if let Some(sanitizer) = sanitizer {
+        let sanitizer_flags =
+            sanitizer_flags(target, sanitizer, static_crt(target)).map_err(|e| {
+                BuildError::Validation {
+                    reason: format!("RUSSIMP_SANITIZER can't be applied: {}.", e),
+                    hint: "Build with a compiler having the sanitizer, or unset RUSSIMP_SANITIZER."
+                        .to_string(),
+                }
+            })?;
+        info!("instrumenting assimp with {}", sanitizer_flags.join(" "));
+        assimp_flags.extend(sanitizer_flags);
+        let rustflags = env::var("CARGO_ENCODED_RUSTFLAGS").unwrap_or_default();
+        let runtime_linked = match sanitizer {
+            "undefined" => rustflags.contains("-fsanitize=undefined"),
+            _ => rustflags.contains(&format!("sanitizer={}", sanitizer)),
+        };
+        if !runtime_linked {
+            let (rustflags, command) = sanitizer_rustflags(target, sanitizer);
+            warning!(
+                "assimp is instrumented with the {} sanitizer, link its runtime with \
+                RUSTFLAGS=\"{}\" {}",
+                sanitizer,
+                rustflags,
+                command
+            );
+        }
+    }
+    if min_size() {
+        let size_flags = size_flags(target);
+        info!(
+            "building assimp for size with {} and {}",
+            size_flags.join(" "),
+            section_gc_flags(target)
+        );
+        assimp_flags.extend(size_flags);
+        if !static_build {
+            defines.push((
+                "CMAKE_SHARED_LINKER_FLAGS_MINSIZEREL",
+                section_gc_flags(target).into(),
+            ));
+        } else {
+            info!("rustc drops the unused sections of the static assimp when it links binaries");
+        }
+    }
+    Ok(assimp_flags)
+}
+
+/// Where the installed libraries and tool find each other, and the C runtime of MSVC.
+fn install_defines(
+    target: &TargetInfo,
+    static_build: bool,
+    cmake: &mut cmake::Config,
+    defines: &mut Vec<(&str, OsString)>,
+) {
+    if target.os == "macos" {
+        // Binaries find the dylib through their rpaths rather than in OUT_DIR.
+        defines.push(("CMAKE_INSTALL_NAME_DIR", "@rpath".into()));
+    }
+    // The installed tool finds the shared assimp in the `lib` directory beside its own, DLLs are
+    // installed next to it.
+    if build_tools() && !static_build && !target.is_windows() {
+        let origin = if target.os == "macos" {
+            "@loader_path"
+        } else {
+            "$ORIGIN"
+        };
+        defines.push(("CMAKE_INSTALL_RPATH", format!("{}/../lib", origin).into()));
+    }
+
+    if target.is_msvc() {
+        let static_crt = static_crt(target);
+        info!(
+            "building assimp against the {} C runtime",
+            if static_crt {
+                "static (/MT)"
+            } else {
+                "DLL (/MD)"
+            }
+        );
+        cmake.static_crt(static_crt);
+        // assimp sets the runtime of its own targets and of the zlib it builds with this.
+        defines.push((
+            "USE_STATIC_CRT",
+            if static_crt { "ON" } else { "OFF" }.into(),
+        ));
+    }
+}
+
+/// Sets up the generator of CMake and returns it, unless it is left to CMake.
+fn assimp_generator(
+    target: &TargetInfo,
+    platform_setup: bool,
+    cmake: &mut cmake::Config,
+    defines: &mut Vec<(&str, OsString)>,
+) -> Option<String> {
+    // RUSSIMP_CMAKE_GENERATOR forces a generator, CMAKE_GENERATOR is left to CMake and Ninja is
+    // preferred otherwise.
+    println!("cargo:rerun-if-env-changed=RUSSIMP_CMAKE_GENERATOR");
+    let forced_generator = env::var("RUSSIMP_CMAKE_GENERATOR")
+        .ok()
+        .map(|generator| generator.trim().to_string())
+        .filter(|generator| !generator.is_empty());
+    let mut generator = configured_generator(
+        target,
+        forced_generator.as_deref(),
+        env::var_os("CMAKE_GENERATOR").is_some(),
+        which::which("ninja").is_ok(),
+        cfg!(windows),
+    );
+    // Ninja builds with the cl.exe in the PATH, which is the host's. The Visual Studio generator is
+    // told the architecture with `-A` by the cmake crate instead.
+    if generator.as_deref() == Some("Ninja")
+        && platform_setup
+        && target.is_msvc()
+        && target.arch != env::consts::ARCH
+    {
+        match cc::windows_registry::find_tool(&target.triple, "cl.exe") {
+            Some(cl) => {
+                // The environment vcvars sets up for the target.
+                for (key, value) in cl.get_envs() {
+                    cmake.env(key, value);
+                }
+                defines.push(("CMAKE_C_COMPILER", cl.path().into()));
+                defines.push(("CMAKE_CXX_COMPILER", cl.path().into()));
+            }
+            None if forced_generator.is_some() => warning!(
+                "no cl.exe found for {}, Ninja builds with the compiler in the PATH",
+                target.triple
+            ),
+            None => {
+                info!(
+                    "no cl.exe found for {}, building with the Visual Studio generator",
+                    target.triple
+                );
+                generator = None;
+            }
+        }
+    }
+    if let Some(generator) = &generator {
+        cmake.generator(generator);
+    }
+    generator
+}
+
+/// Sets the parallel jobs of the build with `build_generator`.
+fn assimp_build_parallelism(
+    build_generator: &str,
+    cmake: &mut cmake::Config,
+) -> Result<(), BuildError> {
+    // Makefiles share the jobserver of cargo unless the jobs are set explicitly, the other
+    // generators run as many jobs as cargo does.
+    let jobserver = build_generator.contains("Makefiles")
+        && env::var_os("CARGO_MAKEFLAGS").is_some()
+        && !cfg!(windows);
+    match assimp_build_jobs()? {
+        Some((jobs, "NUM_JOBS")) if jobserver => info!(
+            "compiling assimp with the jobserver of cargo ({} jobs) and {}",
+            jobs, build_generator
+        ),
+        Some((jobs, source)) => {
+            info!(
+                "compiling assimp with {} parallel jobs ({}) and {}",
+                jobs, source, build_generator
+            );
+            cmake.env("CMAKE_BUILD_PARALLEL_LEVEL", jobs.to_string());
+            for arg in parallel_build_args(build_generator, jobs) {
+                cmake.build_arg(arg);
+            }
+        }
+        None => info!(
+            "compiling assimp with the default parallelism of {}",
+            build_generator
+        ),
+    }
+    Ok(())
+}
+
+/// Removes the CMake cache of `install_dir` unless it was configured for `configuration`.
+fn reset_stale_configuration(install_dir: &Path, configuration: &str) -> Result<(), BuildError> {
+    let components_marker = install_dir.join(".russimp-components");
+    let components_hash = hex(&Sha256::digest(configuration));
+    if fs::read_to_string(&components_marker).ok().as_deref() != Some(components_hash.as_str()) {
+        let _ = fs::remove_file(install_dir.join("build").join("CMakeCache.txt"));
+        fs::create_dir_all(install_dir)
+            .and_then(|()| fs::write(&components_marker, &components_hash))
+            .map_err(|e| BuildError::Bindgen {
+                reason: format!("Unable to write {}: {}", components_marker.display(), e),
+                output: components_marker,
+            })?;
+    }
+    Ok(())
+}
+
+/// Compiles through the launcher of `compiler_launcher()`, if any.
+fn launcher_defines(
+    target: &TargetInfo,
+    out_dir: &Path,
+    defines: &mut Vec<(&str, OsString)>,
+) -> Result<(), BuildError> {
+    match compiler_launcher() {
+        Some((launcher, source)) => {
+            info!(
+                "compiling assimp through the launcher {} ({})",
+                launcher, source
+            );
+            defines.push(("CMAKE_C_COMPILER_LAUNCHER", launcher.clone().into()));
+            defines.push(("CMAKE_CXX_COMPILER_LAUNCHER", launcher.into()));
+            if target.is_msvc() {
+                let script = out_dir.join("russimp-embedded-debug-info.cmake");
+                fs::write(&script, EMBEDDED_DEBUG_INFO).map_err(|e| BuildError::Bindgen {
+                    reason: format!("Unable to write {}: {}", script.display(), e),
+                    output: script.clone(),
+                })?;
+                info!("compiling the debug info of assimp into its objects (/Z7) for the launcher");
+                defines.push(("CMAKE_PROJECT_INCLUDE", script.into()));
+                defines.push(("CMAKE_POLICY_DEFAULT_CMP0141", "NEW".into()));
+                defines.push(("CMAKE_MSVC_DEBUG_INFORMATION_FORMAT", "Embedded".into()));
+                // There are no PDBs left to install.
+                defines.push(("ASSIMP_INSTALL_PDB", "OFF".into()));
+            }
+        }
+        None => info!("compiling assimp without a compiler launcher"),
+    }
+    Ok(())
+}
+
+/// The toolchains, compilers and position independence of the target. Without `platform_setup`,
+/// a toolchain file of the user's own sets up the toolchain and the compilers instead.
+fn platform_defines(
+    target: &TargetInfo,
+    static_build: bool,
+    platform_setup: bool,
+    defines: &mut Vec<(&str, OsString)>,
+) -> Result<(), BuildError> {
+    if target.is_android() {
+        let abi = android_abi(target).ok_or_else(|| BuildError::Validation {
+            reason: format!(
+                "Building assimp for {} is not supported, the NDK has no ABI for it.",
+                target.triple
+            ),
+            hint: "Build for one of the Android targets of the NDK.".to_string(),
+        })?;
+        if platform_setup {
+            let ndk = android_ndk().map_err(|hint| toolchain_error("the Android NDK", hint))?;
+            defines.push((
+                "CMAKE_TOOLCHAIN_FILE",
+                ndk.join(ANDROID_TOOLCHAIN_FILE).into(),
+            ));
+        }
+        defines.push(("ANDROID_ABI", abi.into()));
+        defines.push((
+            "ANDROID_PLATFORM",
+            format!("android-{}", android_api_level()).into(),
+        ));
+        defines.push((
+            "ANDROID_STL",
+            if static_build {
+                "c++_static"
+            } else {
+                "c++_shared"
+            }
+            .into(),
+        ));
+    }
+
+    if platform_setup && (target.env == "musl" || (target.is_windows() && !target.is_msvc())) {
+        let (cc, cxx) = target_compilers(target)?;
+        defines.push(("CMAKE_C_COMPILER", cc.path().into()));
+        defines.push(("CMAKE_CXX_COMPILER", cxx.path().into()));
+    }
+
+    println!("cargo:rerun-if-env-changed=RUSSIMP_PIC");
+    let pic = position_independent_code(
+        target,
+        static_build,
+        env::var("RUSSIMP_PIC").ok().as_deref(),
+    )
+    .map_err(|e| BuildError::Validation {
+        reason: format!("{}.", e),
+        hint: "Set RUSSIMP_PIC to 1 or 0, or unset it.".to_string(),
+    })?;
+    if pic {
+        // The bundled zlib is built with it as well.
+        info!("compiling assimp as position independent code");
+        defines.push(("CMAKE_POSITION_INDEPENDENT_CODE", "ON".into()));
+    }
+
+    if target.is_static_musl() {
+        let (_, cxx) = target_compilers(target)?;
+        match static_cxx_runtime_dir(&cxx) {
+            Some(dir) => emit_link_search(&dir),
+            None => warning!(
+                "{} doesn't know a static libstdc++.a, linking the C++ runtime may fail",
+                cxx.path().display()
+            ),
+        }
+    }
+
+    if target.is_emscripten() && platform_setup {
+        let emscripten =
+            emscripten_root().map_err(|hint| toolchain_error("the Emscripten SDK", hint))?;
+        defines.push((
+            "CMAKE_TOOLCHAIN_FILE",
+            emscripten.join(EMSCRIPTEN_TOOLCHAIN_FILE).into(),
+        ));
+    }
+
+    if target.is_apple_mobile() && platform_setup {
+        let sdk = apple_sdk(target);
+        let sysroot = apple_sdk_path(sdk)
+            .map_err(|hint| toolchain_error(&format!("the {} SDK of Xcode", sdk), hint))?;
+        defines.push((
+            "CMAKE_SYSTEM_NAME",
+            if target.os == "tvos" { "tvOS" } else { "iOS" }.into(),
+        ));
+        defines.push(("CMAKE_OSX_SYSROOT", sysroot.into()));
+        defines.push(("CMAKE_OSX_ARCHITECTURES", apple_arch(target).into()));
+        defines.push((
+            "CMAKE_OSX_DEPLOYMENT_TARGET",
+            apple_deployment_target(target).into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Passes the flags of universal macOS builds to CMake itself, and logs the flags of the others.
+fn flag_defines(
+    target: &TargetInfo,
+    flags: &AssimpFlags,
+    defines: &mut Vec<(&str, OsString)>,
+) -> Result<(), BuildError> {
+    if let Some(architectures) = macos_architectures(target) {
+        if !architectures.iter().any(|arch| arch == apple_arch(target)) {
+            return Err(BuildError::Validation {
+                reason: format!(
+                    "RUSSIMP_MACOS_ARCHITECTURES ({}) doesn't include {}, the architecture of {}.",
+                    architectures.join(";"),
+                    apple_arch(target),
+                    target.triple
+                ),
+                hint: "Add the architecture of the target to RUSSIMP_MACOS_ARCHITECTURES."
+                    .to_string(),
+            });
+        }
+        defines.push(("CMAKE_OSX_ARCHITECTURES", architectures.join(";").into()));
+        // The flags of the cc crate target the architecture of the target only, which would keep
+        // clang from building the others, so only the ones of the user are passed.
+        let cmake_flags = |env_flags: &Option<String>, flags: &[String]| {
+            env_flags
+                .iter()
+                .flat_map(|env_flags| env_flags.split_whitespace())
+                .map(str::to_string)
+                .chain(flags.iter().map(|flag| cmake_flag(flag)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        defines.push((
+            "CMAKE_C_FLAGS",
+            cmake_flags(&flags.env_cflags, &flags.cflags).into(),
+        ));
+        defines.push((
+            "CMAKE_CXX_FLAGS",
+            cmake_flags(&flags.env_cxxflags, &flags.cxxflags).into(),
+        ));
+    } else {
+        // The cmake crate follows the flags of the build script with those of the cc crate, which
+        // include CFLAGS and CXXFLAGS, and leaves the optimization flags to CMake.
+        let (cc, cxx) = target_compilers(target)?;
+        let cmake_flags = |flags: &[String], compiler: &cc::Tool| {
+            flags
+                .iter()
+                .map(|flag| cmake_flag(flag))
+                .chain(
+                    compiler
+                        .args()
+                        .iter()
+                        .map(|arg| arg.to_string_lossy().into_owned())
+                        .filter(|arg| {
+                            !arg.starts_with("-O") && !arg.starts_with("/O") && arg != "-g"
+                        }),
+                )
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        info!(
+            "compiling assimp with CMAKE_C_FLAGS={} CMAKE_CXX_FLAGS={}",
+            cmake_flags(&flags.cflags, &cc),
+            cmake_flags(&flags.cxxflags, &cxx)
+        );
+    }
+    Ok(())
+}
+
+/// Installs the build of the cache `entry` in `install_dir`, if it is there and has the headers
+/// of `wrapper`. The entry is returned when it was used.
+fn cached_build<'a>(
+    entry: Option<&'a Path>,
+    wrapper: &str,
+    install_dir: &Path,
+) -> Option<&'a Path> {
+    entry.filter(|entry| {
+        if !entry.is_dir() {
+            return false;
+        }
+        if env::var("RUSSIMP_FORCE_REBUILD").as_deref() == Ok("1") {
+            info!("rebuilding assimp rather than using {}", entry.display());
+            return false;
+        }
+        match check_wrapper_headers(wrapper, &entry.join("include"))
+            .map_err(|e| e.to_string())
+            .and_then(|()| copy_install(entry, install_dir).map_err(|e| e.to_string()))
+        {
+            Ok(()) => true,
+            Err(e) => {
+                warning!("unable to use the cached assimp {}: {}", entry.display(), e);
+                false
+            }
+        }
+    })
+}
+
+/// Builds and installs assimp, and stores the install in the cache `entry`. Returns the install
+/// directory.
+fn install_assimp(
+    cmake: &mut cmake::Config,
+    assimp_src_dir: &Path,
+    wrapper: &str,
+    cache_entry: Option<&Path>,
+) -> Result<PathBuf, BuildError> {
+    info!("building assimp from {}", assimp_src_dir.display());
+    let cmake_dir = run_cmake(cmake, assimp_src_dir)?;
+    match prune_install(&cmake_dir) {
+        Ok(removed) if !removed.is_empty() => info!(
+            "removed {} of the install of assimp",
+            removed
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Ok(_) => {}
+        Err(e) => warning!("Unable to prune {}: {}", cmake_dir.display(), e),
+    }
+    check_wrapper_headers(wrapper, &cmake_dir.join("include")).map_err(|e| BuildError::CMake {
+        source_dir: assimp_src_dir.to_path_buf(),
+        exit_code: None,
+        reason: format!("The install of assimp is incomplete: {}", e),
+    })?;
+    if let Some(entry) = cache_entry {
+        match store_in_build_cache(&cmake_dir, entry) {
+            Ok(()) => info!("stored the assimp build in {}", entry.display()),
+            Err(e) => warning!(
+                "unable to store the assimp build in {}: {}",
+                entry.display(),
+                e
+            ),
+        }
+    }
+    Ok(cmake_dir)
+}
+
+/// Configures, builds and installs assimp with `cmake`, which panics when CMake fails. Its message
+/// becomes the error, with the exit code of CMake it names.
+fn run_cmake(cmake: &mut cmake::Config, source_dir: &Path) -> Result<PathBuf, BuildError> {
+    // The message is reported with the error instead.
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let built = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| cmake.build()));
+    std::panic::set_hook(hook);
+    built.map_err(|payload| {
+        let message = payload
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| payload.downcast_ref::<&str>().copied())
+            .unwrap_or("CMake failed");
+        cmake_error(message, source_dir)
+    })
+}
+
+/// The error of the panic message of `cmake`, e.g. `command did not execute successfully, got:
+/// exit status: 1` followed by `build script failed, must exit now`.
+pub(crate) fn cmake_error(message: &str, source_dir: &Path) -> BuildError {
+    let reason = message
+        .trim()
+        .trim_end_matches("build script failed, must exit now")
+        .trim();
+    let exit_code = ["exit status: ", "exit code: "].iter().find_map(|prefix| {
+        let code = &reason[reason.find(prefix)? + prefix.len()..];
+        let end = code
+            .find(|c: char| !c.is_ascii_digit() && c != '-')
+            .unwrap_or(code.len());
+        code[..end].parse().ok()
+    });
+    BuildError::CMake {
+        source_dir: source_dir.to_path_buf(),
+        exit_code,
+        reason: reason.to_string(),
+    }
+}
+
+/// The directories of a CMake install of assimp the build script and the package tool use, with the
+/// subdirectories kept in them: the libraries in `lib` and `bin` and the headers in
+/// `include/assimp`. The CMake package files in `lib/cmake`, the pkg-config files in
+/// `lib/pkgconfig` and everything else in them is removed.
+const INSTALL_LAYOUT: &[(&str, &[&str])] = &[("include", &["assimp"]), ("lib", &[]), ("bin", &[])];
+
+/// The directories next to those of `INSTALL_LAYOUT` assimp installs, with documentation.
+const INSTALL_PRUNED: &[&str] = &["share"];
+
+/// Removes what isn't in `INSTALL_LAYOUT` from the install at `install_dir`, returning the paths
+/// removed. The files in the directories of the layout are all kept.
+pub(crate) fn prune_install(install_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    for dir in INSTALL_PRUNED {
+        let path = install_dir.join(dir);
+        if path.is_dir() {
+            fs::remove_dir_all(&path)?;
+            removed.push(path);
+        }
+    }
+    for (dir, kept) in INSTALL_LAYOUT {
+        let Ok(entries) = fs::read_dir(install_dir.join(dir)) else {
+            continue;
+        };
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name();
+            if entry.file_type()?.is_dir() && !kept.iter().any(|kept| name == *kept) {
+                fs::remove_dir_all(entry.path())?;
+                removed.push(entry.path());
+            }
+        }
+    }
+    Ok(removed)
+}
+
+/// Checks that the headers `wrapper` includes, which bindgen reads, are in `include_dir`.
+pub(crate) fn check_wrapper_headers(wrapper: &str, include_dir: &Path) -> Result<(), String> {
+    let missing: Vec<&str> = wrapper
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("#include <")?.strip_suffix('>'))
+        .filter(|header| !include_dir.join(header).is_file())
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} has none of {}",
+            include_dir.display(),
+            missing.join(", ")
+        ))
+    }
+}
+
+/// The directory of the build cache, `RUSSIMP_BUILD_CACHE_DIR`, where the installs of assimp
+/// compiled from source are kept for other `OUT_DIR`s, e.g. the ones of other workspaces, to link
+/// without building it again. It's opt-in, unlike the download cache: `None` without it.
+fn build_cache_dir() -> Option<PathBuf> {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_BUILD_CACHE_DIR");
+    println!("cargo:rerun-if-env-changed=RUSSIMP_FORCE_REBUILD");
+    env::var_os("RUSSIMP_BUILD_CACHE_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The SHA-256 of the source archive assimp is built from, `None` when the source can't be told
+/// by it: a tree in `RUSSIMP_ASSIMP_SOURCE_DIR` may be patched, and `RUSSIMP_ASSIMP_SHA256=skip`
+/// builds any archive. Those are never cached.
+fn cached_source_sha256(version: &str) -> Option<String> {
+    if env::var_os("RUSSIMP_ASSIMP_SOURCE_DIR").is_some() {
+        info!("not caching the build of the assimp source in RUSSIMP_ASSIMP_SOURCE_DIR");
+        return None;
+    }
+    assimp_source_sha256(version).ok().flatten()
+}
+
+/// The name of the compiler `tool` and its version, as the first line it prints: `--version` of
+/// GCC and clang, the banner of MSVC, which has no such option.
+fn compiler_identity(tool: &cc::Tool) -> String {
+    let mut command = std::process::Command::new(tool.path());
+    command.envs(tool.get_envs());
+    if !tool.is_like_msvc() {
+        command.arg("--version");
+    }
+    let version = command
+        .output()
+        .ok()
+        .and_then(|output| {
+            [output.stdout, output.stderr]
+                .iter()
+                .flat_map(|stream| {
+                    String::from_utf8_lossy(stream)
+                        .lines()
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+                .find(|line| !line.trim().is_empty())
+        })
+        .unwrap_or_default();
+    format!("{} {}", tool.path().display(), version.trim())
+}
+
+/// The key of the build cache entry of assimp built from the source archive with `source_sha256`
+/// for `triple` by the `compilers`, with the `configuration` of its defines and flags. The
+/// version of the crate is part of it, as the build script decides what is installed.
+pub(crate) fn build_cache_key(
+    source_sha256: &str,
+    triple: &str,
+    compilers: &[String],
+    configuration: &[String],
+) -> String {
+    hex(&Sha256::digest(format!(
+        "{}\n{}\n{}\n{}\n{}",
+        env!("CARGO_PKG_VERSION"),
+        source_sha256,
+        triple,
+        compilers.join("\n"),
+        configuration.join("\n")
+    )))
+}
+
+/// How long a build waits for the lock of a build cache entry before it takes it over, as one
+/// left behind by a build that was killed. Locks are only held while an install is copied.
+pub(crate) const BUILD_CACHE_LOCK_TIMEOUT: std::time::Duration =
+    std::time::Duration::from_secs(600);
+
+/// Copies the directory tree at `src` to `dest`, recreating symlinks like `copy_shared_library`.
+fn copy_tree(src: &Path, dest: &Path) -> io::Result<()> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        #[cfg(unix)]
+        if file_type.is_symlink() {
+            let _ = fs::remove_file(&target);
+            std::os::unix::fs::symlink(fs::read_link(entry.path())?, &target)?;
+            continue;
+        }
+        if file_type.is_dir() {
+            copy_tree(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copies the directories of `INSTALL_LAYOUT` of the assimp install at `src` to `dest`, replacing
+/// those of an earlier install there.
+pub(crate) fn copy_install(src: &Path, dest: &Path) -> io::Result<()> {
+    for (dir, _) in INSTALL_LAYOUT {
+        let dest_dir = dest.join(dir);
+        if dest_dir.exists() {
+            fs::remove_dir_all(&dest_dir)?;
+        }
+        if src.join(dir).is_dir() {
+            copy_tree(&src.join(dir), &dest_dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// Stores the assimp install at `install_dir` as the build cache `entry`. It's copied to a
+/// directory of this build first and renamed to the entry at once, so concurrent builds never see
+/// a partial entry, and the lock of the entry lets one build store it at a time. An entry stored
+/// meanwhile is kept, unless `RUSSIMP_FORCE_REBUILD` replaces it.
+pub(crate) fn store_in_build_cache(install_dir: &Path, entry: &Path) -> io::Result<()> {
+    let name = entry.file_name().unwrap().to_string_lossy().into_owned();
+    let cache_dir = entry.parent().unwrap();
+    fs::create_dir_all(cache_dir)?;
+    let _lock = FileLock::acquire(
+        &cache_dir.join(format!("{}.lock", name)),
+        BUILD_CACHE_LOCK_TIMEOUT,
+    )?;
+    if entry.is_dir() {
+        if env::var("RUSSIMP_FORCE_REBUILD").as_deref() != Ok("1") {
+            return Ok(());
+        }
+        fs::remove_dir_all(entry)?;
+    }
+    let staging = cache_dir.join(format!("{}.tmp-{}", name, std::process::id()));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    let stored = copy_install(install_dir, &staging).and_then(|()| fs::rename(&staging, entry));
+    if stored.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    stored
+}
+
+/// The assimp libraries of the install at `install_dir` and their sizes, without the symlinks to
+/// them.
+pub(crate) fn assimp_library_sizes(install_dir: &Path) -> Vec<(PathBuf, u64)> {
+    let mut sizes: Vec<_> = ["lib", "bin"]
+        .iter()
+        .filter_map(|subdir| fs::read_dir(install_dir.join(subdir)).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+            (name.starts_with("assimp") || name.starts_with("libassimp")) && !name.ends_with(".pdb")
+        })
+        .filter_map(|entry| {
+            let metadata = fs::symlink_metadata(entry.path()).ok()?;
+            metadata
+                .file_type()
+                .is_file()
+                .then(|| (entry.path(), metadata.len()))
+        })
+        .collect();
+    sizes.sort();
+    sizes
+}
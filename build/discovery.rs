@@ -0,0 +1,1361 @@
+//! Locating the build tools, libclang, and a system assimp and zlib.
+
+use super::*;
+
+/// The cargo directives linking the system minizip, which assimp uses with `minizip` left out.
+pub(crate) fn locate_system_minizip() -> Result<Vec<String>, String> {
+    let library = pkg_config::Config::new()
+        .cargo_metadata(false)
+        .probe("minizip")
+        .map_err(|e| format!("pkg-config could not locate the system minizip: {}", e))?;
+    Ok(library
+        .link_paths
+        .iter()
+        .map(|dir| format!("cargo:rustc-link-search=native={}", dir.display()))
+        .chain(
+            library
+                .libs
+                .iter()
+                .map(|lib| format!("cargo:rustc-link-lib={}", lib)),
+        )
+        .collect())
+}
+
+/// The CMake binary the cmake crate runs: `CMAKE`, or `cmake` from the PATH.
+fn cmake_program() -> OsString {
+    println!("cargo:rerun-if-env-changed=CMAKE");
+    env::var_os("CMAKE")
+        .filter(|cmake| !cmake.is_empty())
+        .unwrap_or_else(|| "cmake".into())
+}
+
+/// Whether `file_name` is the libclang bindgen loads, e.g. `libclang.so.14`, `libclang-14.so`,
+/// `libclang.dylib` or `libclang.dll`, rather than the C++ `libclang-cpp`.
+pub(crate) fn is_libclang(file_name: &str) -> bool {
+    let name = file_name.strip_prefix("lib").unwrap_or(file_name);
+    let Some(rest) = name.strip_prefix("clang") else {
+        return false;
+    };
+    let shared = rest.contains(".so") || rest.ends_with(".dylib") || rest.ends_with(".dll");
+    shared && (rest.starts_with('.') || (rest.starts_with('-') && !rest.starts_with("-cpp")))
+}
+
+/// The subdirectories of `parent` whose names start with `prefix`, joined with `suffix`, e.g. the
+/// `lib` directories of the `/usr/lib/llvm-*` installs of Debian.
+fn prefixed_dirs(parent: &str, prefix: &str, suffix: &str) -> Vec<PathBuf> {
+    fs::read_dir(parent)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(prefix))
+        .map(|entry| entry.path().join(suffix))
+        .collect()
+}
+
+/// The oldest libclang the bindgen of the build script supports.
+const MIN_LIBCLANG_VERSION: u32 = 5;
+
+/// The libclang bindgen would generate the bindings with, as `probe_libclang` finds it.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Libclang {
+    Found,
+    Missing,
+    /// The libclang at the path, which is older than `MIN_LIBCLANG_VERSION`.
+    TooOld(PathBuf, u32),
+}
+
+/// The major version in the name of the libclang `file_name`, e.g. 14 of `libclang.so.14.0.6` or
+/// `libclang-14.so.1`. The unversioned `libclang.so`, `libclang.dylib` and `libclang.dll` tell none.
+pub(crate) fn libclang_version(file_name: &str) -> Option<u32> {
+    let name = file_name.strip_prefix("lib").unwrap_or(file_name);
+    let rest = name.strip_prefix("clang")?;
+    let version = rest
+        .strip_prefix('-')
+        .or_else(|| rest.split_once(".so.").map(|(_, version)| version))?;
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// The libclang among the files of `dirs`, as `list_dir` lists them, along with the version
+/// `llvm-config` tells for its directories. Any one bindgen supports will do, like clang-sys it
+/// takes the one that is new enough when there are several.
+pub(crate) fn find_libclang(
+    dirs: &[(PathBuf, Option<u32>)],
+    list_dir: impl Fn(&Path) -> Vec<String>,
+) -> Libclang {
+    let mut too_old = None;
+    for (dir, dir_version) in dirs {
+        for name in list_dir(dir).into_iter().filter(|name| is_libclang(name)) {
+            match libclang_version(&name).or(*dir_version) {
+                Some(version) if version < MIN_LIBCLANG_VERSION => {
+                    too_old.get_or_insert((dir.join(name), version));
+                }
+                _ => return Libclang::Found,
+            }
+        }
+    }
+    match too_old {
+        Some((path, version)) => Libclang::TooOld(path, version),
+        None => Libclang::Missing,
+    }
+}
+
+/// Looks for the libclang bindgen generates the bindings with where clang-sys looks for it: at
+/// `LIBCLANG_PATH`, in the directories of `llvm-config`, on the library search path and in the
+/// usual install locations of LLVM.
+pub(crate) fn probe_libclang() -> Libclang {
+    println!("cargo:rerun-if-env-changed=LIBCLANG_PATH");
+    println!("cargo:rerun-if-env-changed=LLVM_CONFIG_PATH");
+    // The directories to search, along with the version llvm-config tells for its own.
+    let mut dirs: Vec<(PathBuf, Option<u32>)> = Vec::new();
+    if let Some(path) = env::var_os("LIBCLANG_PATH").map(PathBuf::from) {
+        if path.is_file() {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            return match libclang_version(&name) {
+                Some(version) if version < MIN_LIBCLANG_VERSION => Libclang::TooOld(path, version),
+                _ => Libclang::Found,
+            };
+        }
+        dirs.push((path, None));
+    }
+
+    let llvm_config = env::var_os("LLVM_CONFIG_PATH").unwrap_or_else(|| "llvm-config".into());
+    let llvm_config = |arg: &str| {
+        std::process::Command::new(&llvm_config)
+            .arg(arg)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let llvm_version = llvm_config("--version").and_then(|version| {
+        version
+            .split('.')
+            .next()
+            .and_then(|major| major.parse::<u32>().ok())
+    });
+    for arg in ["--libdir", "--bindir"] {
+        if let Some(dir) = llvm_config(arg) {
+            dirs.push((PathBuf::from(dir), llvm_version));
+        }
+    }
+    let mut unversioned_dirs = Vec::new();
+    for var in ["LD_LIBRARY_PATH", "DYLD_FALLBACK_LIBRARY_PATH", "PATH"] {
+        unversioned_dirs.extend(env::var_os(var).iter().flat_map(env::split_paths));
+    }
+
+    if cfg!(windows) {
+        for program_files in ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"] {
+            if let Some(dir) = env::var_os(program_files) {
+                unversioned_dirs.push(PathBuf::from(dir).join("LLVM").join("bin"));
+            }
+        }
+    } else if cfg!(target_os = "macos") {
+        unversioned_dirs.extend(
+            [
+                "/Library/Developer/CommandLineTools/usr/lib",
+                "/Applications/Xcode.app/Contents/Developer/Toolchains/XcodeDefault.xctoolchain/usr/lib",
+                "/opt/homebrew/opt/llvm/lib",
+                "/usr/local/opt/llvm/lib",
+            ]
+            .map(PathBuf::from),
+        );
+    } else {
+        unversioned_dirs.extend(["/usr/lib", "/usr/lib64", "/usr/local/lib"].map(PathBuf::from));
+        unversioned_dirs.extend(prefixed_dirs("/usr/lib", "llvm", "lib"));
+        unversioned_dirs.extend(prefixed_dirs("/usr/lib64", "llvm", "lib"));
+        unversioned_dirs.extend(prefixed_dirs("/usr/local", "llvm", "lib"));
+        // The multiarch directories of Debian, e.g. /usr/lib/x86_64-linux-gnu.
+        unversioned_dirs.extend(prefixed_dirs(
+            "/usr/lib",
+            &format!("{}-", env::consts::ARCH),
+            "",
+        ));
+    }
+
+    dirs.extend(unversioned_dirs.into_iter().map(|dir| (dir, None)));
+
+    find_libclang(&dirs, |dir| {
+        fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect()
+    })
+}
+
+/// The tools the enabled features need that aren't installed: CMake to build assimp from source,
+/// Ninja when CMake is told to generate for it, and the `libclang` for bindgen in every mode.
+pub(crate) fn missing_build_tools(libclang: &Libclang) -> Vec<&'static str> {
+    let mut missing = Vec::new();
+    if build_assimp() {
+        if which::which(cmake_program()).is_err() {
+            missing.push("cmake");
+        }
+        println!("cargo:rerun-if-env-changed=CMAKE_GENERATOR");
+        println!("cargo:rerun-if-env-changed=RUSSIMP_CMAKE_GENERATOR");
+        let ninja_generator = ["RUSSIMP_CMAKE_GENERATOR", "CMAKE_GENERATOR"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|generator| !generator.trim().is_empty())
+            .is_some_and(|generator| generator.trim().starts_with("Ninja"));
+        if ninja_generator && which::which("ninja").is_err() {
+            missing.push("ninja");
+        }
+    }
+    if *libclang == Libclang::Missing {
+        missing.push("libclang");
+    }
+    missing
+}
+
+/// The commands installing the `tools` on the platform the build runs on.
+pub(crate) fn install_commands(tools: &[&str]) -> Vec<String> {
+    // The package managers of the platform, and their packages of cmake, ninja and libclang.
+    let managers: &[(&str, [&str; 3])] = if cfg!(windows) {
+        &[("choco install", ["cmake", "ninja", "llvm"])]
+    } else if cfg!(target_os = "macos") {
+        &[("brew install", ["cmake", "ninja", "llvm"])]
+    } else if cfg!(any(
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "dragonfly"
+    )) {
+        &[("pkg install", ["cmake", "ninja", "llvm"])]
+    } else {
+        &[
+            ("sudo apt install", ["cmake", "ninja-build", "libclang-dev"]),
+            ("sudo dnf install", ["cmake", "ninja-build", "clang-devel"]),
+        ]
+    };
+    managers
+        .iter()
+        .map(|(install, packages)| {
+            let packages: Vec<&str> = ["cmake", "ninja", "libclang"]
+                .iter()
+                .zip(packages)
+                .filter(|(tool, _)| tools.contains(tool))
+                .map(|(_, package)| *package)
+                .collect();
+            format!("{} {}", install, packages.join(" "))
+        })
+        .collect()
+}
+
+pub(crate) fn missing_tools_error(missing: &[&str]) -> BuildError {
+    BuildError::Discovery {
+        missing: missing.join(" and "),
+        probes: Vec::new(),
+        hint: format!(
+            "Install {} with:{}\n\nSet CMAKE to the path of cmake, or LIBCLANG_PATH to the directory \
+            of libclang, if installed elsewhere.",
+            if missing.len() == 1 { "it" } else { "them" },
+            install_commands(missing)
+                .iter()
+                .map(|command| format!("\n    {}", command))
+                .collect::<String>()
+        ),
+    }
+}
+
+pub(crate) fn old_libclang_error(path: &Path, version: u32) -> BuildError {
+    BuildError::Validation {
+        reason: format!(
+            "The libclang {} is version {}, bindgen needs libclang {} or later.",
+            path.display(),
+            version,
+            MIN_LIBCLANG_VERSION
+        ),
+        hint: format!(
+            "Install a newer one with:{}\n\nSet LIBCLANG_PATH to the directory of libclang, or \
+            LLVM_CONFIG_PATH to its llvm-config, if installed elsewhere.",
+            install_commands(&["libclang"])
+                .iter()
+                .map(|command| format!("\n    {}", command))
+                .collect::<String>()
+        ),
+    }
+}
+
+/// Describes what is missing to build assimp from source, if anything.
+pub(crate) fn missing_source_toolchain() -> Option<String> {
+    let mut missing = Vec::new();
+    if which::which(cmake_program()).is_err() {
+        missing.push("cmake");
+    }
+
+    println!("cargo:rerun-if-env-changed=CXX");
+    let has_cxx = match env::var_os("CXX") {
+        Some(cxx) => which::which(cxx).is_ok(),
+        None => ["c++", "g++", "clang++", "cl"]
+            .iter()
+            .any(|cxx| which::which(cxx).is_ok()),
+    };
+    if !has_cxx {
+        missing.push("a C++ compiler");
+    }
+
+    if missing.is_empty() {
+        None
+    } else {
+        Some(format!("{} not found", missing.join(" and ")))
+    }
+}
+
+/// The directories of an assimp install selected with `ASSIMP_DIR`, or individually with
+/// `RUSSIMP_INCLUDE_DIR` and `RUSSIMP_LIB_DIR`, which take precedence over it, or otherwise
+/// provided through the environment of the compiler and linker.
+#[derive(Default)]
+pub(crate) struct AssimpDirs {
+    pub(crate) include_dir: Option<PathBuf>,
+    pub(crate) lib_dir: Option<PathBuf>,
+}
+
+/// Reads the assimp install selected for linking in the default mode from the environment, and
+/// checks it contains the headers and the library to link.
+pub(crate) fn assimp_dirs(target: &TargetInfo) -> Result<AssimpDirs, String> {
+    for var in ["ASSIMP_DIR", "RUSSIMP_INCLUDE_DIR", "RUSSIMP_LIB_DIR"] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+
+    let assimp_dir = env::var_os("ASSIMP_DIR").map(PathBuf::from);
+    let dir = |var: &'static str, subdir: &str| {
+        env::var_os(var)
+            .map(|dir| (var, PathBuf::from(dir)))
+            .or_else(|| Some(("ASSIMP_DIR", assimp_dir.as_ref()?.join(subdir))))
+    };
+
+    let include_dir = dir("RUSSIMP_INCLUDE_DIR", "include");
+    if let Some((var, include_dir)) = &include_dir {
+        validate_include_dir(var, include_dir)?;
+        info!("using the assimp headers in {}", include_dir.display());
+    }
+
+    let lib_dir =
+        dir("RUSSIMP_LIB_DIR", "lib").map(|(var, lib_dir)| (var, arch_lib_dir(target, &lib_dir)));
+    if let Some((var, lib_dir)) = &lib_dir {
+        validate_lib_dir(target, var, lib_dir)?;
+        info!("linking the assimp library in {}", lib_dir.display());
+    }
+
+    // Otherwise look where the environment points the compiler and linker, e.g. in a Nix shell.
+    let environment = environment_assimp_dirs(target);
+    Ok(AssimpDirs {
+        include_dir: include_dir.map(|(_, dir)| dir).or(environment.include_dir),
+        lib_dir: lib_dir.map(|(_, dir)| dir).or(environment.lib_dir),
+    })
+}
+
+/// The directories following one of the `options`, e.g. `-I` or `-isystem`, in the compiler or
+/// linker `flags`, attached or as the next argument.
+pub(crate) fn flag_dirs(flags: &str, options: &[&str]) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut args = flags.split_whitespace();
+    while let Some(arg) = args.next() {
+        for option in options {
+            if arg == *option {
+                dirs.extend(args.next().map(PathBuf::from));
+                break;
+            } else if let Some(dir) = arg.strip_prefix(option) {
+                dirs.push(PathBuf::from(dir));
+                break;
+            }
+        }
+    }
+    dirs
+}
+
+/// The assimp headers and library provided through the environment of the compiler and linker,
+/// as Nix and Guix set it up instead of installing into FHS locations: `NIX_CFLAGS_COMPILE`,
+/// `CPATH` and `C_INCLUDE_PATH` for the headers, `NIX_LDFLAGS` and `LIBRARY_PATH` for the library.
+fn environment_assimp_dirs(target: &TargetInfo) -> AssimpDirs {
+    let vars = [
+        "NIX_CFLAGS_COMPILE",
+        "CPATH",
+        "C_INCLUDE_PATH",
+        "NIX_LDFLAGS",
+        "LIBRARY_PATH",
+    ];
+    for var in vars {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    let flags =
+        |var: &str, options: &[&str]| flag_dirs(&env::var(var).unwrap_or_default(), options);
+    let paths =
+        |var: &str| env::split_paths(&env::var_os(var).unwrap_or_default()).collect::<Vec<_>>();
+
+    let include_dir = flags("NIX_CFLAGS_COMPILE", &["-isystem", "-I"])
+        .into_iter()
+        .chain(paths("CPATH"))
+        .chain(paths("C_INCLUDE_PATH"))
+        .find(|dir| dir.join("assimp").join("scene.h").is_file());
+    let lib_dir = flags("NIX_LDFLAGS", &["-L"])
+        .into_iter()
+        .chain(paths("LIBRARY_PATH"))
+        .find(|dir| contains_assimp_library(target, dir));
+
+    if let Some(include_dir) = &include_dir {
+        info!(
+            "using the assimp headers from the environment in {}",
+            include_dir.display()
+        );
+    }
+    if let Some(lib_dir) = &lib_dir {
+        info!(
+            "linking the assimp library from the environment in {}",
+            lib_dir.display()
+        );
+    }
+    AssimpDirs {
+        include_dir,
+        lib_dir,
+    }
+}
+
+/// Checks that the include directory selected with `var` contains the assimp headers.
+pub(crate) fn validate_include_dir(var: &str, dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!(
+            "{} selects {} as assimp include directory, which doesn't exist",
+            var,
+            dir.display()
+        ));
+    }
+    if !dir.join("assimp").join("scene.h").is_file() {
+        return Err(format!(
+            "{} selects {} as assimp include directory, which doesn't contain assimp/scene.h",
+            var,
+            dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// The patterns of the assimp libraries the lib directory of a system install may contain.
+pub(crate) fn system_library_patterns(target: &TargetInfo) -> Vec<String> {
+    let mut patterns: Vec<String> = assimp_library(target, true)
+        .file_patterns(target)
+        .into_iter()
+        .filter(|(subdir, _)| *subdir == "lib")
+        .map(|(_, pattern)| pattern)
+        .collect();
+    if target.is_msvc() {
+        patterns.push("assimp-vc*.lib".to_string());
+    }
+    // A Debug configuration of CMake names the library `assimpd`.
+    patterns.extend(library_files(target, "assimpd"));
+    patterns
+}
+
+/// The Debian multiarch tuple of a Linux `target`, e.g. `x86_64-linux-gnu`, naming the library
+/// directory of its architecture.
+pub(crate) fn multiarch_tuple(target: &str) -> Option<String> {
+    let mut parts = target.split('-');
+    let arch = parts.next()?;
+    let env = parts.next_back()?;
+    if !target.contains("-linux-") {
+        return None;
+    }
+
+    let arch = match arch {
+        "i586" | "i686" => "i386",
+        arch if arch.starts_with("armv7") || arch == "arm" => "arm",
+        arch if arch.starts_with("riscv64") => "riscv64",
+        arch => arch,
+    };
+    Some(format!("{}-linux-{}", arch, env))
+}
+
+/// The library directories below `prefixes`, i.e. `lib`, `lib64` and the multiarch one for
+/// `target`, that contain an assimp library. Distributions put it in different ones, not all of
+/// which the linker searches in every setup.
+pub(crate) fn linux_library_dirs(target: &TargetInfo, prefixes: &[PathBuf]) -> Vec<PathBuf> {
+    let mut subdirs = vec![PathBuf::from("lib"), PathBuf::from("lib64")];
+    subdirs.extend(multiarch_tuple(&target.triple).map(|tuple| Path::new("lib").join(tuple)));
+
+    prefixes
+        .iter()
+        .flat_map(|prefix| subdirs.iter().map(move |subdir| prefix.join(subdir)))
+        .filter(|dir| contains_assimp_library(target, dir))
+        .collect()
+}
+
+/// Whether `dir` contains a library of a system assimp.
+pub(crate) fn contains_assimp_library(target: &TargetInfo, dir: &Path) -> bool {
+    let patterns = system_library_patterns(target);
+    fs::read_dir(dir).into_iter().flatten().any(|entry| {
+        entry.is_ok_and(|entry| {
+            let file = entry.file_name();
+            patterns
+                .iter()
+                .any(|pattern| matches_pattern(&file.to_string_lossy(), pattern))
+        })
+    })
+}
+
+/// Checks that the lib directory selected with `var` contains the assimp library to link.
+pub(crate) fn validate_lib_dir(target: &TargetInfo, var: &str, dir: &Path) -> Result<(), String> {
+    let files: Vec<String> = fs::read_dir(dir)
+        .map_err(|e| {
+            format!(
+                "{} selects {} as assimp lib directory, which can't be read: {}",
+                var,
+                dir.display(),
+                e
+            )
+        })?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+
+    let patterns = system_library_patterns(target);
+    if !files.iter().any(|file| {
+        patterns
+            .iter()
+            .any(|pattern| matches_pattern(file, pattern))
+    }) {
+        return Err(format!(
+            "{} selects {} as assimp lib directory, which contains no file matching {}, found [{}]",
+            var,
+            dir.display(),
+            patterns.join(" or "),
+            files.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// The file names the linker looks for when linking `name`.
+pub(crate) fn library_files(target: &TargetInfo, name: &str) -> Vec<String> {
+    if target.is_msvc() {
+        vec![format!("{}.lib", name)]
+    } else if target.is_windows() {
+        vec![format!("lib{}.a", name), format!("lib{}.dll.a", name)]
+    } else {
+        ["a", "so", "dylib"]
+            .iter()
+            .map(|extension| format!("lib{}.{}", name, extension))
+            .collect()
+    }
+}
+
+/// Picks the name to link `name` as among the `files` of the lib directories: its debug build,
+/// e.g. `assimpd` or `zlibstaticd` as a Debug configuration of CMake names them, when the release
+/// build is missing or a `debug` build is requested, the name as is otherwise.
+pub(crate) fn resolve_library_name(
+    target: &TargetInfo,
+    name: &str,
+    files: &[String],
+    debug: bool,
+) -> String {
+    let installed = |name: &str| {
+        library_files(target, name)
+            .iter()
+            .any(|file| files.iter().any(|f| f == file))
+    };
+    let debug_name = format!("{}d", name);
+    if !installed(&debug_name) {
+        return name.to_string();
+    }
+
+    if !installed(name) {
+        info!(
+            "linking {}, only the debug build of {} is installed",
+            debug_name, name
+        );
+        debug_name
+    } else if debug {
+        info!(
+            "linking {}, the debug build of {}, for the debug profile",
+            debug_name, name
+        );
+        debug_name
+    } else {
+        name.to_string()
+    }
+}
+
+/// The toolset of the MSVC in use, e.g. `143` for Visual Studio 2022, as found in the names of
+/// the libraries it builds. Read from the version of the tools set up in a developer prompt, or the
+/// path of the `cl.exe` cc finds otherwise.
+pub(crate) fn msvc_toolset(target: &TargetInfo) -> Option<String> {
+    println!("cargo:rerun-if-env-changed=VCToolsVersion");
+    let tools_version = env::var("VCToolsVersion").ok().or_else(|| {
+        let cl = cc::windows_registry::find_tool(&target.triple, "cl.exe")?;
+        // .../VC/Tools/MSVC/14.38.33130/bin/Hostx64/x64/cl.exe
+        let mut components = cl.path().iter().map(|c| c.to_string_lossy().into_owned());
+        components.find(|c| c == "MSVC")?;
+        components.next()
+    })?;
+
+    let mut parts = tools_version.split('.');
+    let major = parts.next()?;
+    let minor = parts.next()?.chars().next()?;
+    Some(format!("{}{}", major, minor))
+}
+
+/// Picks the import library of an assimp installed on Windows among the `files` of its lib
+/// directory, which are named after the toolset and runtime, e.g. `assimp-vc143-mt.lib`, or
+/// `assimp-vc142-mtd.lib` for debug builds. The library of `toolset` and of the `debug` variant is
+/// preferred, then the newest toolset. Returns the name to link, or `None` when the plain
+/// `assimp.lib` is there or no assimp library at all.
+pub(crate) fn msvc_library_name(
+    files: &[String],
+    toolset: Option<&str>,
+    debug: bool,
+) -> Option<String> {
+    if files
+        .iter()
+        .any(|file| file.eq_ignore_ascii_case("assimp.lib"))
+    {
+        return None;
+    }
+
+    files
+        .iter()
+        .filter_map(|file| {
+            let name = file.strip_suffix(".lib")?;
+            let (file_toolset, runtime) = name.strip_prefix("assimp-vc")?.split_once('-')?;
+            if !file_toolset.chars().all(|c| c.is_ascii_digit()) || !runtime.starts_with("mt") {
+                return None;
+            }
+            let file_debug = runtime.ends_with('d');
+            Some((
+                (
+                    toolset == Some(file_toolset),
+                    file_debug == debug,
+                    file_toolset.parse::<u32>().ok(),
+                ),
+                name,
+            ))
+        })
+        .max_by_key(|(rank, _)| *rank)
+        .map(|(_, name)| name.to_string())
+}
+
+/// The name to link assimp as among the `files` of its lib directories. Those `installed`, rather
+/// than built from source, are named after the toolset on Windows.
+pub(crate) fn assimp_link_name(
+    target: &TargetInfo,
+    files: &[String],
+    installed: bool,
+    debug: bool,
+) -> String {
+    let msvc_name = if target.is_msvc() && installed {
+        msvc_library_name(files, msvc_toolset(target).as_deref(), debug)
+    } else {
+        None
+    };
+    msvc_name.unwrap_or_else(|| resolve_library_name(target, "assimp", files, debug))
+}
+
+/// The Homebrew and MacPorts prefixes to search for assimp when targeting macOS. Homebrew is asked
+/// for its prefix since it differs between Apple silicon (`/opt/homebrew`) and Intel (`/usr/local`).
+pub(crate) fn macos_package_prefixes(target: &TargetInfo) -> Vec<PathBuf> {
+    let mut prefixes = Vec::new();
+    if target.os != "macos" {
+        return prefixes;
+    }
+
+    if which::which("brew").is_ok() {
+        // The keg of the assimp formula, or the prefix formulae are linked into otherwise.
+        prefixes
+            .extend(brew_prefix(&["--prefix", "assimp"]).or_else(|| brew_prefix(&["--prefix"])));
+    } else {
+        // Without `brew` in the PATH, e.g. in a build sandbox, its default prefix for the target.
+        prefixes.extend(
+            Some(PathBuf::from(homebrew_default_prefix(target))).filter(|prefix| prefix.is_dir()),
+        );
+    }
+
+    let macports = Path::new("/opt/local");
+    if macports.join("lib").is_dir() {
+        prefixes.push(macports.to_path_buf());
+    }
+
+    info!("macOS package prefixes: {:?}", prefixes);
+    prefixes
+}
+
+/// The prefixes the package systems of the BSDs install into, that contain an assimp library:
+/// `/usr/local` for the ports of FreeBSD and OpenBSD, where X11 libraries live in `/usr/X11R6`,
+/// and `/usr/pkg` for pkgsrc on NetBSD.
+pub(crate) fn bsd_package_prefixes(target: &TargetInfo) -> Vec<PathBuf> {
+    let prefixes: &[&str] = match target.os.as_str() {
+        "freebsd" | "dragonfly" => &["/usr/local"],
+        "openbsd" => &["/usr/local", "/usr/X11R6"],
+        "netbsd" => &["/usr/pkg"],
+        _ => &[],
+    };
+    prefixes
+        .iter()
+        .map(PathBuf::from)
+        .filter(|prefix| contains_assimp_library(target, &prefix.join("lib")))
+        .collect()
+}
+
+/// The prefix Homebrew installs into by default on `target`: `/opt/homebrew` on Apple silicon,
+/// `/usr/local` on Intel Macs.
+pub(crate) fn homebrew_default_prefix(target: &TargetInfo) -> &'static str {
+    if target.arch == "aarch64" {
+        "/opt/homebrew"
+    } else {
+        "/usr/local"
+    }
+}
+
+/// Runs `brew` with `args` and returns the prefix it prints, if it exists.
+fn brew_prefix(args: &[&str]) -> Option<PathBuf> {
+    let output = std::process::Command::new("brew")
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // `brew --prefix <formula>` prints the keg path even when the formula isn't installed.
+    let prefix = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    prefix.is_dir().then_some(prefix)
+}
+
+/// A system assimp located by a package manager, which already emitted the link search paths and
+/// the libraries to link.
+pub(crate) struct LocatedAssimp {
+    pub(crate) include_dirs: Vec<PathBuf>,
+    pub(crate) link_dirs: Vec<PathBuf>,
+    /// The version the package manager reports, if it does.
+    pub(crate) version: Option<String>,
+    /// The libraries of `lib_names()` the package manager emitted as well.
+    pub(crate) linked: &'static [&'static str],
+}
+
+/// Locates the system assimp with the package manager for the target: vcpkg on MSVC, pkg-config
+/// elsewhere except Windows. Every probe is recorded in `attempts`, the details of a failure are
+/// logged.
+pub(crate) fn probe_package_manager(
+    target: &TargetInfo,
+    attempts: &mut Vec<String>,
+) -> Option<LocatedAssimp> {
+    if target.is_msvc() {
+        probe_vcpkg(attempts)
+    } else if !target.is_windows() {
+        probe_pkg_config(attempts)
+    } else {
+        None
+    }
+}
+
+fn probe_pkg_config(attempts: &mut Vec<String>) -> Option<LocatedAssimp> {
+    match pkg_config::Config::new().probe("assimp") {
+        Ok(library) => {
+            attempts.push(format!(
+                "pkg-config found assimp {} in {:?}",
+                library.version, library.link_paths
+            ));
+            Some(LocatedAssimp {
+                include_dirs: library.include_paths,
+                link_dirs: library.link_paths,
+                version: Some(library.version),
+                linked: &["assimp"],
+            })
+        }
+        Err(e) => {
+            // The pkg-config error spans many lines with hints, only show it on request.
+            info!("{}", e);
+            attempts.push("pkg-config could not locate assimp".to_string());
+            None
+        }
+    }
+}
+
+/// The vcpkg crate picks the static or dynamic triplet from the `crt-static` target feature,
+/// `VCPKGRS_DYNAMIC` and `VCPKGRS_TRIPLET`, and the libraries to link, e.g. `assimp-vc143-mt`,
+/// from the files of the port.
+fn probe_vcpkg(attempts: &mut Vec<String>) -> Option<LocatedAssimp> {
+    for var in ["VCPKG_ROOT", "VCPKGRS_DYNAMIC", "VCPKGRS_TRIPLET"] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+
+    match vcpkg::Config::new().find_package("assimp") {
+        Ok(library) => {
+            attempts.push(format!(
+                "vcpkg found assimp ({}) in {:?}",
+                library.vcpkg_triplet, library.link_paths
+            ));
+            Some(LocatedAssimp {
+                include_dirs: library.include_paths,
+                link_dirs: library.link_paths,
+                version: None,
+                // The port links its own zlib.
+                linked: &["assimp", WINDOWS_ZLIB],
+            })
+        }
+        Err(e) => {
+            info!("{}", e);
+            attempts.push("vcpkg could not locate assimp".to_string());
+            None
+        }
+    }
+}
+
+/// The system zlib `nozlib` builds assimp against, located before the build so CMake is pointed to
+/// the same one that is linked.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SystemZlib {
+    /// The prefix selected with `ZLIB_ROOT`.
+    root: Option<PathBuf>,
+    pub(crate) include_dir: Option<PathBuf>,
+    /// The library file, unless it is in a default linker search path the locator left out.
+    library: Option<PathBuf>,
+    /// The cargo directives linking it, emitted after assimp so the linker resolves its symbols.
+    pub(crate) cargo_metadata: Vec<String>,
+}
+
+impl SystemZlib {
+    /// The CMake definitions making `FindZLIB` of the assimp build find this zlib.
+    pub(crate) fn defines(&self) -> Vec<(&'static str, OsString)> {
+        [
+            ("ZLIB_ROOT", &self.root),
+            ("ZLIB_INCLUDE_DIR", &self.include_dir),
+            ("ZLIB_LIBRARY", &self.library),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.as_ref().map(|value| (key, value.into())))
+        .collect()
+    }
+}
+
+/// The names zlib is installed as: `zlib` by its CMake build on Windows, `zlibstatic` for its
+/// static library there and `zdll` by older builds, `z` everywhere else.
+pub(crate) fn zlib_library_names(target: &TargetInfo) -> &'static [&'static str] {
+    if target.is_windows() {
+        &["zlib", "zlibstatic", "zdll", "z"]
+    } else {
+        &["z"]
+    }
+}
+
+/// The zlib of `lib_names` on Windows, linked as `resolve_windows_zlib` finds it installed.
+pub(crate) const WINDOWS_ZLIB: &str = "zlib";
+
+/// Finds the zlib of a Windows install in the `listings` of the files in its directories, as the
+/// name to link and its kind: `zlibstatic` and `zdll` name the static and import libraries of its
+/// builds, `zlib` and `z` are import libraries when a zlib DLL is next to them or in the `bin`
+/// directory beside, static ones otherwise. The `libz.dll.a` of MSYS2 is preferred over its
+/// `libz.a`, unless the `linkage` of `zlib-static` or `zlib-dynamic` only accepts the other kind.
+pub(crate) fn resolve_windows_zlib(
+    target: &TargetInfo,
+    listings: &[(PathBuf, Vec<String>)],
+    debug: bool,
+    linkage: Option<&str>,
+) -> Option<(String, &'static str)> {
+    let has_dll = |dir: &Path| {
+        let bin_dir = dir.parent().map(|parent| parent.join("bin"));
+        listings
+            .iter()
+            .filter(|(listed, _)| listed == dir || Some(listed) == bin_dir.as_ref())
+            .flat_map(|(_, files)| files)
+            .map(|file| file.to_ascii_lowercase())
+            .any(|file| {
+                file.ends_with(".dll") && (file.starts_with("zlib") || file.starts_with("libz"))
+            })
+    };
+    listings.iter().find_map(|(dir, files)| {
+        zlib_library_names(target).iter().find_map(|name| {
+            let name = resolve_library_name(target, name, files, debug);
+            let installed = library_files(target, &name)
+                .into_iter()
+                .filter(|file| files.contains(file))
+                .filter(|file| match linkage {
+                    // MinGW installs both kinds of libz side by side.
+                    Some("static") if !target.is_msvc() => !file.ends_with(".dll.a"),
+                    Some("dylib") if !target.is_msvc() => {
+                        !file.ends_with(".a") || file.ends_with(".dll.a")
+                    }
+                    _ => true,
+                })
+                .collect::<Vec<_>>();
+            let kind = if installed.is_empty() {
+                return None;
+            } else if name.starts_with("zlibstatic") {
+                "static"
+            } else if name.starts_with("zdll")
+                || installed.iter().any(|file| file.ends_with(".dll.a"))
+                || (target.is_msvc() && has_dll(dir))
+            {
+                "dylib"
+            } else {
+                "static"
+            };
+            (linkage.is_none() || linkage == Some(kind)).then_some((name, kind))
+        })
+    })
+}
+
+/// Finds the zlib library file in `dirs`, preferring the libraries of `kind` where both are
+/// installed, and the name and kind it is linked as.
+pub(crate) fn find_zlib_library(
+    target: &TargetInfo,
+    dirs: &[PathBuf],
+    kind: &str,
+) -> Option<(PathBuf, String, &'static str)> {
+    let is_archive = |file: &str| file.ends_with(".a") && !file.ends_with(".dll.a");
+    dirs.iter().find_map(|dir| {
+        zlib_library_names(target).iter().find_map(|name| {
+            let mut files = library_files(target, name);
+            files.sort_by_key(|file| is_archive(file) != (kind == "static"));
+            files.into_iter().find_map(|file| {
+                let kind = if is_archive(&file) || *name == "zlibstatic" {
+                    "static"
+                } else {
+                    "dylib"
+                };
+                let path = dir.join(file);
+                path.is_file().then(|| (path, name.to_string(), kind))
+            })
+        })
+    })
+}
+
+/// The zlib installed to the prefix `root`, as `ZLIB_ROOT` selects it for CMake as well.
+pub(crate) fn zlib_from_root(
+    target: &TargetInfo,
+    root: &Path,
+    kind: &str,
+) -> Result<SystemZlib, String> {
+    let include_dir = root.join("include");
+    if !include_dir.join("zlib.h").is_file() {
+        return Err(format!(
+            "ZLIB_ROOT={} contains no include/zlib.h",
+            root.display()
+        ));
+    }
+    let lib_dirs = [root.join("lib"), root.join("lib64")];
+    let (library, name, kind) = find_zlib_library(target, &lib_dirs, kind).ok_or_else(|| {
+        format!(
+            "ZLIB_ROOT={} contains no {} library in lib/",
+            root.display(),
+            zlib_library_names(target).join(", ")
+        )
+    })?;
+    Ok(SystemZlib {
+        root: Some(root.to_path_buf()),
+        include_dir: Some(include_dir),
+        cargo_metadata: vec![
+            format!(
+                "cargo:rustc-link-search=native={}",
+                library.parent().unwrap().display()
+            ),
+            format!("cargo:rustc-link-lib={}={}", kind, name),
+        ],
+        library: Some(library),
+    })
+}
+
+/// Locates the system zlib for `nozlib`: the prefix in `ZLIB_ROOT`, else vcpkg on MSVC and
+/// pkg-config elsewhere, preferring the libraries of `kind`. Every probe is recorded in
+/// `attempts`.
+pub(crate) fn locate_system_zlib(
+    target: &TargetInfo,
+    kind: &str,
+    attempts: &mut Vec<String>,
+) -> Option<SystemZlib> {
+    println!("cargo:rerun-if-env-changed=ZLIB_ROOT");
+    if let Some(root) = env::var_os("ZLIB_ROOT").filter(|root| !root.is_empty()) {
+        return match zlib_from_root(target, Path::new(&root), kind) {
+            Ok(zlib) => Some(zlib),
+            Err(e) => {
+                attempts.push(e);
+                None
+            }
+        };
+    }
+
+    if target.is_msvc() {
+        for var in ["VCPKG_ROOT", "VCPKGRS_DYNAMIC", "VCPKGRS_TRIPLET"] {
+            println!("cargo:rerun-if-env-changed={}", var);
+        }
+        match vcpkg::Config::new()
+            .cargo_metadata(false)
+            .find_package("zlib")
+        {
+            Ok(library) => Some(SystemZlib {
+                include_dir: library.include_paths.first().cloned(),
+                library: library.found_libs.first().cloned(),
+                cargo_metadata: library.cargo_metadata,
+                ..SystemZlib::default()
+            }),
+            Err(e) => {
+                attempts.push(format!("vcpkg could not locate zlib: {}", e));
+                None
+            }
+        }
+    } else {
+        match pkg_config::Config::new()
+            .cargo_metadata(false)
+            .statik(kind == "static")
+            .probe("zlib")
+        {
+            Ok(library) => {
+                // pkg-config leaves out the default search paths, where CMake finds zlib as well.
+                let library_file = find_zlib_library(target, &library.link_paths, kind);
+                let cargo_metadata = library
+                    .link_paths
+                    .iter()
+                    .map(|dir| format!("cargo:rustc-link-search=native={}", dir.display()))
+                    .chain(
+                        library
+                            .libs
+                            .iter()
+                            .map(|lib| format!("cargo:rustc-link-lib={}", lib)),
+                    )
+                    .collect();
+                Some(SystemZlib {
+                    include_dir: library.include_paths.first().cloned(),
+                    library: library_file.map(|(path, _, _)| path),
+                    cargo_metadata,
+                    ..SystemZlib::default()
+                })
+            }
+            Err(e) => {
+                info!("{}", e);
+                attempts.push("pkg-config could not locate zlib".to_string());
+                None
+            }
+        }
+    }
+}
+
+pub(crate) fn zlib_error(probes: &[String]) -> BuildError {
+    BuildError::Discovery {
+        missing: "the system zlib the `nozlib` and `zlib-dynamic` features build assimp against"
+            .to_string(),
+        probes: probes.to_vec(),
+        hint: "Install the development package of zlib (e.g. `zlib1g-dev`, `zlib-devel`, `brew \
+            install zlib` or `vcpkg install zlib`), select its install prefix with ZLIB_ROOT, or \
+            disable them to build the zlib bundled with assimp."
+            .to_string(),
+    }
+}
+
+/// Reads the version of the assimp headers in `include_dir` from `assimp/revision.h`.
+pub(crate) fn header_version(include_dir: &Path) -> Result<String, String> {
+    let revision_h = include_dir.join("assimp").join("revision.h");
+    let revision = fs::read_to_string(&revision_h)
+        .map_err(|e| format!("unable to read {}: {}", revision_h.display(), e))?;
+
+    let define = |name: &str| {
+        revision.lines().find_map(|line| {
+            let mut words = line.split_whitespace();
+            (words.next() == Some("#define") && words.next() == Some(name))
+                .then(|| words.next())
+                .flatten()
+        })
+    };
+    match (
+        define("VER_MAJOR"),
+        define("VER_MINOR"),
+        define("VER_PATCH"),
+    ) {
+        (Some(major), Some(minor), Some(patch)) => Ok(format!("{}.{}.{}", major, minor, patch)),
+        _ => Err(format!(
+            "{} doesn't define VER_MAJOR, VER_MINOR and VER_PATCH",
+            revision_h.display()
+        )),
+    }
+}
+
+/// Finds the headers of the system assimp, those reported by the package manager that located it or
+/// else the ones in the `package_prefixes` of macOS and the BSDs or the default install location on
+/// Windows, whose lib dir is added to `link_dirs`. Every probe is recorded in `attempts`.
+pub(crate) fn system_headers(
+    target: &TargetInfo,
+    located_assimp: &Option<LocatedAssimp>,
+    package_prefixes: &[PathBuf],
+    link_dirs: &mut Vec<PathBuf>,
+    attempts: &mut Vec<String>,
+) -> Option<PathBuf> {
+    let located_headers = located_assimp
+        .iter()
+        .flat_map(|located| &located.include_dirs)
+        .find(|dir| dir.join("assimp").join("scene.h").is_file());
+    if let Some(include_dir) = located_headers {
+        return Some(include_dir.clone());
+    }
+
+    // The lib dirs of the package prefixes are searched already.
+    let windows_prefix = env::var_os("ProgramFiles")
+        .filter(|_| target.is_windows())
+        .map(|dir| PathBuf::from(dir).join("Assimp"));
+    if let Some(prefix) = &windows_prefix {
+        link_dirs.push(arch_lib_dir(target, &prefix.join("lib")));
+    }
+    for prefix in package_prefixes.iter().chain(&windows_prefix) {
+        let include_dir = prefix.join("include");
+        if include_dir.join("assimp").join("version.h").is_file() {
+            return Some(include_dir);
+        }
+        attempts.push(format!(
+            "no assimp headers in {}",
+            include_dir.join("assimp").display()
+        ));
+    }
+    None
+}
+
+/// Locates the preinstalled assimp the `system` feature requires, through the selected
+/// directories, the package manager or the well-known install locations, without ever fetching or
+/// building anything. Returns its include directory and version, or every probe attempted when
+/// there is no compatible installation.
+pub(crate) fn require_system_assimp(
+    target: &TargetInfo,
+    assimp_dirs: &AssimpDirs,
+    located_assimp: &mut Option<LocatedAssimp>,
+    package_prefixes: &[PathBuf],
+    link_dirs: &mut Vec<PathBuf>,
+) -> Result<(PathBuf, String), Vec<String>> {
+    let mut attempts = Vec::new();
+    if assimp_dirs.lib_dir.is_none() {
+        *located_assimp = probe_package_manager(target, &mut attempts);
+    }
+
+    let include_dir = match &assimp_dirs.include_dir {
+        Some(include_dir) => Some(include_dir.clone()),
+        None => system_headers(
+            target,
+            located_assimp,
+            package_prefixes,
+            link_dirs,
+            &mut attempts,
+        ),
+    };
+    let Some(include_dir) = include_dir else {
+        return Err(attempts);
+    };
+
+    // The bindings are generated from the system headers, but russimp is written against this major
+    // version of the API.
+    let required_major = ASSIMP_VERSION.split('.').next().unwrap();
+    match header_version(&include_dir) {
+        Ok(version) if version.split('.').next() == Some(required_major) => {
+            info!(
+                "linking the system assimp {} in {}",
+                version,
+                include_dir.display()
+            );
+            Ok((include_dir, version))
+        }
+        Ok(version) => {
+            attempts.push(format!(
+                "assimp {} in {} is incompatible, assimp {}.x is required",
+                version,
+                include_dir.display(),
+                required_major
+            ));
+            Err(attempts)
+        }
+        Err(e) => {
+            attempts.push(e);
+            Err(attempts)
+        }
+    }
+}
+
+/// Whether two assimp versions share the major and minor version, which the layout of structs like
+/// `aiScene` only changes with.
+pub(crate) fn compatible_versions(a: &str, b: &str) -> bool {
+    let minor = |version: &str| {
+        let mut parts = version.trim_start_matches('v').split('.');
+        (
+            parts.next().map(str::to_string),
+            parts.next().map(str::to_string),
+        )
+    };
+    minor(a) == minor(b)
+}
+
+/// Builds and runs a program printing the version of the assimp library found in `link_dirs`.
+/// Fails when cross-compiling, as the program can't run then.
+fn probe_library_version(
+    target: &TargetInfo,
+    include_dirs: &[PathBuf],
+    link_dirs: &[PathBuf],
+    out_dir: &Path,
+) -> Result<String, String> {
+    if env::var("TARGET") != env::var("HOST") {
+        return Err("the version probe can't run when cross-compiling".to_string());
+    }
+
+    let probe_dir = out_dir.join("version-probe");
+    fs::create_dir_all(&probe_dir).map_err(|e| e.to_string())?;
+    let source = probe_dir.join("probe.c");
+    // `aiGetVersionPatch()` is missing from older releases, the minor version is what matters.
+    fs::write(
+        &source,
+        "#include <stdio.h>\n#include <assimp/version.h>\n\
+        int main(void) { printf(\"%u.%u\", aiGetVersionMajor(), aiGetVersionMinor()); return 0; }\n",
+    )
+    .map_err(|e| e.to_string())?;
+    let probe = probe_dir.join(if cfg!(windows) { "probe.exe" } else { "probe" });
+    // The library the crate links, not necessarily named `assimp`.
+    let lib_files: Vec<String> = link_dirs
+        .iter()
+        .flat_map(|dir| fs::read_dir(dir).into_iter().flatten())
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    let debug = env::var("PROFILE").as_deref() == Ok("debug");
+    let link_name = assimp_link_name(target, &lib_files, true, debug);
+
+    let compiler = cc::Build::new()
+        .cargo_metadata(false)
+        .try_get_compiler()
+        .map_err(|e| e.to_string())?;
+    let mut command = compiler.to_command();
+    if compiler.is_like_msvc() {
+        command
+            .arg(&source)
+            .arg(joined_path_arg("/Fe", &probe))
+            .arg(joined_path_arg("/Fo", &probe_dir.join("probe.obj")));
+        for dir in include_dirs {
+            command.arg("/I").arg(dir);
+        }
+        command.arg("/link");
+        command.args(
+            link_dirs
+                .iter()
+                .map(|dir| joined_path_arg("/LIBPATH:", dir)),
+        );
+        command.arg(format!("{}.lib", link_name));
+    } else {
+        command.arg(&source).arg("-o").arg(&probe);
+        for dir in include_dirs {
+            command.arg("-I").arg(dir);
+        }
+        for dir in link_dirs {
+            command.arg("-L").arg(dir);
+        }
+        command.arg(format!("-l{}", link_name));
+    }
+    let output = command.output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "the version probe failed to build: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    // The shared library has to be found when running the probe as well.
+    let library_path_var = if cfg!(windows) {
+        "PATH"
+    } else if cfg!(target_os = "macos") {
+        "DYLD_LIBRARY_PATH"
+    } else {
+        "LD_LIBRARY_PATH"
+    };
+    let library_path = env::join_paths(link_dirs.iter().cloned().chain(env::split_paths(
+        &env::var_os(library_path_var).unwrap_or_default(),
+    )))
+    .map_err(|e| e.to_string())?;
+    let output = std::process::Command::new(&probe)
+        .env(library_path_var, library_path)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "the version probe failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Fails the build when the linked system assimp differs in its major or minor version from the
+/// headers the bindings are generated from, the bindings would silently corrupt memory otherwise.
+/// The version of the library is probed, or taken from `package_version`, found through the
+/// package manager, when the probe isn't possible. `RUSSIMP_SKIP_VERSION_CHECK` skips the check.
+pub(crate) fn check_linked_version(
+    target: &TargetInfo,
+    header_version: Option<&str>,
+    include_dirs: &[PathBuf],
+    link_dirs: &[PathBuf],
+    package_version: Option<String>,
+    out_dir: &Path,
+) -> Result<(), BuildError> {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_SKIP_VERSION_CHECK");
+    if env::var_os("RUSSIMP_SKIP_VERSION_CHECK").is_some() {
+        return Ok(());
+    }
+    let Some(header_version) = header_version else {
+        info!("the version of the assimp headers is unknown, skipping the version check");
+        return Ok(());
+    };
+
+    let library_version = match probe_library_version(target, include_dirs, link_dirs, out_dir) {
+        Ok(version) => version,
+        Err(e) => {
+            info!("{}", e);
+            match package_version {
+                Some(version) => version,
+                None => {
+                    warning!(
+                        "unable to determine the version of the linked assimp, skipping the version check"
+                    );
+                    return Ok(());
+                }
+            }
+        }
+    };
+
+    if !compatible_versions(&library_version, header_version) {
+        return Err(BuildError::Validation {
+            reason: format!(
+                "The linked assimp {} doesn't match the assimp {} headers the bindings are generated \
+                from, their structs differ and using them would corrupt memory.",
+                library_version, header_version
+            ),
+            hint: "Link a matching assimp, select the headers of the linked one with ASSIMP_DIR or \
+                RUSSIMP_INCLUDE_DIR, or set RUSSIMP_SKIP_VERSION_CHECK=1 if you know they are \
+                compatible."
+                .to_string(),
+        });
+    }
+    info!(
+        "the linked assimp {} matches the assimp {} headers",
+        library_version, header_version
+    );
+    Ok(())
+}
+
+/// The headers to generate the bindings from are neither installed nor fetched with the source.
+pub(crate) fn headers_error(e: Box<dyn std::error::Error>, probes: &[String]) -> BuildError {
+    BuildError::Discovery {
+        missing: "the assimp headers to generate the bindings from".to_string(),
+        probes: probes.to_vec(),
+        hint: format!(
+            "Fetching the assimp source for them failed ({}). Install the assimp development headers \
+            with your package manager (e.g. libassimp-dev or assimp-devel), select them with \
+            ASSIMP_DIR or RUSSIMP_INCLUDE_DIR, or enable the `build-assimp` feature.",
+            e
+        ),
+    }
+}
+
+pub(crate) fn system_error(probes: &[String]) -> BuildError {
+    BuildError::Discovery {
+        missing: format!(
+            "the preinstalled assimp {}.x of the `system` feature",
+            ASSIMP_VERSION.split('.').next().unwrap()
+        ),
+        probes: probes.to_vec(),
+        hint:
+            "Install it with your package manager, or select its directories with ASSIMP_DIR, or \
+            RUSSIMP_INCLUDE_DIR and RUSSIMP_LIB_DIR."
+                .to_string(),
+    }
+}
@@ -0,0 +1,1088 @@
+//! Downloading and unpacking the assimp source, and the download cache shared by the builds.
+
+use super::*;
+
+/// GitHub API endpoint describing the newest assimp release, queried for `RUSSIMP_ASSIMP_VERSION=latest`.
+const ASSIMP_LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/assimp/assimp/releases/latest";
+
+/// The assimp version (without the leading `v` of the git tag) to fetch the source for.
+///
+/// `RUSSIMP_ASSIMP_VERSION=latest` resolves the newest release through the GitHub API.
+pub(crate) fn assimp_version() -> Result<String, Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_ASSIMP_VERSION");
+
+    match env::var("RUSSIMP_ASSIMP_VERSION") {
+        Ok(version) if version.trim().eq_ignore_ascii_case("latest") => latest_assimp_version(),
+        Ok(version) if !version.trim().is_empty() => {
+            Ok(version.trim().trim_start_matches('v').to_string())
+        }
+        _ => Ok(ASSIMP_VERSION.to_string()),
+    }
+}
+
+fn latest_assimp_version() -> Result<String, Box<dyn std::error::Error>> {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let release_path = out_dir.join("assimp-latest-release.json");
+    download_file(ASSIMP_LATEST_RELEASE_URL, &release_path)?;
+    let release = fs::read_to_string(&release_path)?;
+    let _ = fs::remove_file(&release_path);
+
+    let tag = json_string_field(&release, "tag_name").ok_or_else(|| {
+        format!(
+            "The response from {} does not contain a tag_name",
+            ASSIMP_LATEST_RELEASE_URL
+        )
+    })?;
+    info!("resolved the latest assimp release to {}", tag);
+    Ok(tag.trim_start_matches('v').to_string())
+}
+
+/// Extracts the value of a top-level string field from a JSON document, which is all the release
+/// lookup needs and not worth a JSON parser as build dependency.
+pub(crate) fn json_string_field<'a>(json: &'a str, field: &str) -> Option<&'a str> {
+    let key = format!("\"{}\"", field);
+    let rest = json[json.find(&key)? + key.len()..].trim_start();
+    let rest = rest.strip_prefix(':')?.trim_start().strip_prefix('"')?;
+    rest.find('"').map(|end| &rest[..end])
+}
+
+pub(crate) fn assimp_source_url(version: &str) -> String {
+    format!(
+        "https://github.com/assimp/assimp/archive/refs/tags/v{}.tar.gz",
+        version
+    )
+}
+
+/// The SHA-256 the downloaded source archive has to match, or `None` when verification was
+/// explicitly disabled by setting `RUSSIMP_ASSIMP_SHA256=skip`.
+pub(crate) fn assimp_source_sha256(
+    version: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_ASSIMP_SHA256");
+
+    match env::var("RUSSIMP_ASSIMP_SHA256") {
+        Ok(sha) if sha.trim().eq_ignore_ascii_case("skip") => Ok(None),
+        Ok(sha) if !sha.trim().is_empty() => Ok(Some(sha.trim().to_ascii_lowercase())),
+        _ if version == ASSIMP_VERSION => Ok(Some(ASSIMP_SOURCE_SHA256.to_string())),
+        _ => Err(format!(
+            "No known SHA-256 for the assimp v{} source archive. \
+            Set RUSSIMP_ASSIMP_SHA256 to the expected digest, or to \"skip\" to disable verification.",
+            version
+        )
+        .into()),
+    }
+}
+
+#[cfg(feature = "download")]
+const PROXY_ENV_VARS: &[&str] = &[
+    "RUSSIMP_PROXY",
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+    "NO_PROXY",
+    "no_proxy",
+];
+
+/// The proxy to use for `url`, if any, with the proxy variables looked up in `lookup`.
+///
+/// `RUSSIMP_PROXY` wins over everything, otherwise the conventional `HTTPS_PROXY`/`HTTP_PROXY`/
+/// `ALL_PROXY` variables (and their lowercase spellings) are honored unless the host is listed in
+/// `NO_PROXY`, as a domain and its subdomains, optionally with a port, or `*`. Loopback hosts are
+/// never proxied.
+#[cfg(feature = "download")]
+pub(crate) fn proxy_for(url: &str, lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    let var = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| lookup(name).filter(|value| !value.trim().is_empty()))
+    };
+
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let https = scheme.eq_ignore_ascii_case("https");
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let (host, port) = split_host_port(authority);
+    let host = host.to_ascii_lowercase();
+    let port = port.or(Some(if https { "443" } else { "80" }));
+
+    if host == "localhost"
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+    {
+        return None;
+    }
+    if let Some(proxy) = var(&["RUSSIMP_PROXY"]) {
+        return Some(proxy);
+    }
+
+    if let Some(no_proxy) = var(&["NO_PROXY", "no_proxy"]) {
+        let excluded = no_proxy.split(',').map(str::trim).any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+            let entry = entry.trim_start_matches('*').trim_start_matches('.');
+            let (entry_host, entry_port) = split_host_port(entry);
+            let entry_host = entry_host.to_ascii_lowercase();
+            !entry_host.is_empty()
+                && (entry_port.is_none() || entry_port == port)
+                && (host == entry_host || host.ends_with(&format!(".{}", entry_host)))
+        });
+        if excluded {
+            return None;
+        }
+    }
+
+    if https {
+        var(&["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"])
+    } else {
+        var(&["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"])
+    }
+}
+
+/// Splits `authority` into its host and port, e.g. `[::1]:8080` into `::1` and `8080`. A bare IPv6
+/// address has no port.
+#[cfg(feature = "download")]
+fn split_host_port(authority: &str) -> (&str, Option<&str>) {
+    if let Some(bracketed) = authority.strip_prefix('[') {
+        let (host, rest) = bracketed.split_once(']').unwrap_or((bracketed, ""));
+        return (host, rest.strip_prefix(':'));
+    }
+    match authority.split_once(':') {
+        Some((host, port)) if !port.contains(':') => (host, Some(port)),
+        Some(_) => (authority, None),
+        None => (authority, None),
+    }
+}
+
+/// How often a download is attempted before giving up.
+#[cfg(feature = "download")]
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// Downloads `url` to `dest`, returning the SHA-256 of the downloaded file.
+///
+/// All downloads of the build script go through here, so proxy handling, timeouts, retries and
+/// error reporting are the same for the source archive and the prebuilt packages. The response is
+/// streamed to `<dest>.partial` and hashed on the way, and only renamed to `dest` once complete.
+/// An interrupted download is resumed from the partial file when the server supports range
+/// requests.
+#[cfg(feature = "download")]
+pub(crate) fn download_file(url: &str, dest: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    for name in PROXY_ENV_VARS {
+        println!("cargo:rerun-if-env-changed={}", name);
+    }
+    let proxy = proxy_for(url, |name| env::var(name).ok());
+    let via = match &proxy {
+        // Don't leak proxy credentials into the build log.
+        Some(proxy) => match proxy.rsplit_once('@') {
+            Some((_, host)) => format!(" (via proxy <credentials>@{})", host),
+            None => format!(" (via proxy {})", proxy),
+        },
+        None => String::new(),
+    };
+
+    // Proxies are resolved by `proxy_for` alone, the agent doesn't pick up its own.
+    let mut agent = ureq::AgentBuilder::new().timeout(std::time::Duration::from_secs(300));
+    if let Some(proxy) = &proxy {
+        agent = agent
+            .proxy(ureq::Proxy::new(proxy).map_err(|e| format!("Invalid proxy{}: {}", via, e))?);
+    }
+    let agent = agent.build();
+
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+
+    let mut last_error = String::new();
+    let mut last_status = None;
+    for attempt in 1..=DOWNLOAD_ATTEMPTS {
+        if attempt > 1 {
+            let delay = std::time::Duration::from_secs(1 << (attempt - 1));
+            warning!(
+                "download of {} failed ({}), retrying in {}s (attempt {}/{})",
+                url,
+                last_error,
+                delay.as_secs(),
+                attempt,
+                DOWNLOAD_ATTEMPTS
+            );
+            std::thread::sleep(delay);
+        }
+
+        match download_attempt(&agent, url, &partial) {
+            Ok(sha256) => {
+                fs::rename(&partial, dest)?;
+                return Ok(sha256);
+            }
+            Err(e) => {
+                last_error = e.to_string();
+                last_status = e.status;
+                if !e.retryable {
+                    break;
+                }
+            }
+        }
+    }
+
+    let message = format!("Failed to download {}{}: {}", url, via, last_error);
+    if last_status == Some(404) {
+        return Err(NotFound(message).into());
+    }
+    Err(message.into())
+}
+
+/// A download failing because the file doesn't exist on the server.
+#[derive(Debug)]
+#[cfg_attr(not(feature = "download"), allow(dead_code))]
+pub(crate) struct NotFound(String);
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+/// Stand-in used when the build script was compiled without an HTTP client.
+#[cfg(not(feature = "download"))]
+pub(crate) fn download_file(url: &str, _dest: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    Err(format!(
+        "Unable to download {}: downloads require the `download` feature (enabled by `build-assimp` and `prebuilt`)",
+        url
+    )
+    .into())
+}
+
+/// A failed `download_attempt`, with whether trying again could help.
+#[cfg(feature = "download")]
+struct DownloadError {
+    message: String,
+    retryable: bool,
+    status: Option<u16>,
+}
+
+#[cfg(feature = "download")]
+impl std::fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+#[cfg(feature = "download")]
+impl<E: std::error::Error> From<E> for DownloadError {
+    fn from(e: E) -> Self {
+        DownloadError {
+            message: e.to_string(),
+            retryable: true,
+            status: None,
+        }
+    }
+}
+
+/// A single attempt of `download_file`, appending to `partial` if the server honors the range request.
+#[cfg(feature = "download")]
+fn download_attempt(
+    agent: &ureq::Agent,
+    url: &str,
+    partial: &Path,
+) -> Result<String, DownloadError> {
+    let resume_from = fs::metadata(partial).map(|m| m.len()).unwrap_or(0);
+    let mut request = agent.get(url);
+    if resume_from > 0 {
+        request = request.set("Range", &format!("bytes={}-", resume_from));
+    }
+
+    let response = match request.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(416, _)) => {
+            // The partial file doesn't match what the server has, start over on the next attempt.
+            fs::remove_file(partial)?;
+            return Err(DownloadError {
+                message: "the server rejected resuming the partial download".to_string(),
+                retryable: true,
+                status: Some(416),
+            });
+        }
+        Err(ureq::Error::Status(status, response)) => {
+            return Err(DownloadError {
+                message: format!("HTTP {} {}", status, response.status_text()),
+                // Client errors such as a 404 won't go away by asking again.
+                retryable: status >= 500 || status == 408 || status == 429,
+                status: Some(status),
+            });
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut hasher = Sha256::new();
+    let mut file = if resume_from > 0 && response.status() == 206 {
+        info!("resuming download of {} at {} bytes", url, resume_from);
+        // The digest has to cover the data downloaded by earlier attempts as well.
+        io::copy(&mut fs::File::open(partial)?, &mut hasher)?;
+        fs::OpenOptions::new().append(true).open(partial)?
+    } else {
+        fs::File::create(partial)?
+    };
+
+    // A partial response only reports the length of the remaining data.
+    let existing = file.metadata()?.len();
+    let total = response
+        .header("Content-Length")
+        .and_then(|len| len.parse::<u64>().ok())
+        .map(|len| len + existing);
+    copy_with_progress(
+        &mut response.into_reader(),
+        &mut file,
+        &mut hasher,
+        url,
+        total,
+    )?;
+    Ok(hex(&hasher.finalize()))
+}
+
+/// Progress of a download is reported at most this often.
+#[cfg(feature = "download")]
+const PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Copies `reader` to the end of `file`, feeding `hasher` along the way, while periodically
+/// reporting the download progress unless `RUSSIMP_NO_PROGRESS` is set.
+#[cfg(feature = "download")]
+fn copy_with_progress(
+    reader: &mut impl io::Read,
+    file: &mut fs::File,
+    hasher: &mut Sha256,
+    url: &str,
+    total: Option<u64>,
+) -> io::Result<()> {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_NO_PROGRESS");
+    let quiet = env::var_os("RUSSIMP_NO_PROGRESS").is_some();
+
+    let start = std::time::Instant::now();
+    let mut last_report = start;
+    let mut downloaded = file.metadata()?.len();
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        io::Write::write_all(file, &buffer[..read])?;
+        hasher.update(&buffer[..read]);
+        downloaded += read as u64;
+
+        if !quiet && last_report.elapsed() >= PROGRESS_INTERVAL {
+            last_report = std::time::Instant::now();
+
+            let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+            let size = match total {
+                Some(total) if total > 0 => format!(
+                    "{:.1} / {:.1} MiB ({}%)",
+                    mib(downloaded),
+                    mib(total),
+                    downloaded * 100 / total
+                ),
+                _ => format!("{:.1} MiB", mib(downloaded)),
+            };
+            warning!(
+                "downloading {}: {}, {}s elapsed",
+                url,
+                size,
+                start.elapsed().as_secs()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// A lock serializing the build scripts writing to a path shared between them, e.g. an entry of
+/// the download or build cache: a file created by one of them only, removed when it's dropped.
+/// Concurrent cargo invocations and build scripts of differently configured dependents otherwise
+/// overwrite each other's partial downloads and copies. A lock older than its `stale_after` is
+/// left behind by a build that was killed, and taken over.
+pub(crate) struct FileLock(PathBuf);
+
+impl FileLock {
+    pub(crate) fn acquire(path: &Path, stale_after: std::time::Duration) -> io::Result<Self> {
+        let mut waiting = false;
+        loop {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)
+            {
+                Ok(mut file) => {
+                    // Only tells who holds it.
+                    let _ =
+                        io::Write::write_all(&mut file, std::process::id().to_string().as_bytes());
+                    return Ok(FileLock(path.to_path_buf()));
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    let age = fs::metadata(path)
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok());
+                    if age.is_some_and(|age| age > stale_after) {
+                        warning!("taking over the stale lock {}", path.display());
+                        let _ = fs::remove_file(path);
+                        continue;
+                    }
+                    if !waiting {
+                        waiting = true;
+                        info!(
+                            "waiting for the lock {} of another build (process {})",
+                            path.display(),
+                            fs::read_to_string(path).unwrap_or_default()
+                        );
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// How long a build waits for the lock of a download before it takes it over: longer than all
+/// attempts of a download of the largest archive take.
+const DOWNLOAD_LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3600);
+
+/// Directory downloaded archives are cached in across builds, shared by every `OUT_DIR`.
+///
+/// Defaults to `$CARGO_HOME/russimp-sys-cache` and can be overridden with `RUSSIMP_CACHE_DIR`.
+/// Returns `None` when the directory can't be written to, in which case downloads stay local
+/// to `OUT_DIR`.
+fn cache_dir() -> Option<PathBuf> {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_CACHE_DIR");
+
+    let dir = match env::var_os("RUSSIMP_CACHE_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME")
+                    .or_else(|| env::var_os("USERPROFILE"))
+                    .map(|home| PathBuf::from(home).join(".cargo"))
+            })?
+            .join("russimp-sys-cache"),
+    };
+
+    let probe = dir.join(format!(".write-probe-{}", std::process::id()));
+    let writable = fs::create_dir_all(&dir).is_ok() && fs::write(&probe, "").is_ok();
+    let _ = fs::remove_file(&probe);
+
+    if writable {
+        Some(dir)
+    } else {
+        warning!(
+            "cache directory {} is not writable, downloading into OUT_DIR instead",
+            dir.display()
+        );
+        None
+    }
+}
+
+/// The size the download cache is kept below: `RUSSIMP_CACHE_LIMIT` MiB, 2 GiB by default.
+fn cache_limit() -> u64 {
+    const DEFAULT_CACHE_LIMIT_MIB: u64 = 2048;
+    println!("cargo:rerun-if-env-changed=RUSSIMP_CACHE_LIMIT");
+    let mib = match env::var("RUSSIMP_CACHE_LIMIT") {
+        Ok(limit) => limit.trim().parse().unwrap_or_else(|_| {
+            warning!(
+                "RUSSIMP_CACHE_LIMIT={:?} is not a number of MiB, keeping the cache below {} MiB",
+                limit,
+                DEFAULT_CACHE_LIMIT_MIB
+            );
+            DEFAULT_CACHE_LIMIT_MIB
+        }),
+        Err(_) => DEFAULT_CACHE_LIMIT_MIB,
+    };
+    mib.saturating_mul(1024 * 1024)
+}
+
+/// Fetches `url` into the download cache at `cache_key` (or into `fallback_dir` if the cache is
+/// unusable) and returns the local path of the archive.
+///
+/// `verify` is called with the path and the SHA-256 of the archive. A cached archive is only
+/// reused when it passes `verify`, corrupt entries are evicted and downloaded again.
+pub(crate) fn fetch_archive(
+    url: &str,
+    cache_key: &Path,
+    fallback_dir: &Path,
+    verify: impl Fn(&Path, &str) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let cache = cache_dir().map(|dir| (dir, cache_limit()));
+    fetch_archive_in(
+        cache.as_ref().map(|(dir, limit)| (dir.as_path(), *limit)),
+        url,
+        cache_key,
+        fallback_dir,
+        verify,
+    )
+}
+
+/// [`fetch_archive`] with the download `cache` directory and its size limit, if usable. Once an
+/// archive was downloaded into it, the least recently used ones are evicted past the limit.
+pub(crate) fn fetch_archive_in(
+    cache: Option<(&Path, u64)>,
+    url: &str,
+    cache_key: &Path,
+    fallback_dir: &Path,
+    verify: impl Fn(&Path, &str) -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = match cache {
+        Some((dir, _)) => dir.join(cache_key),
+        None => fallback_dir.join(cache_key.file_name().unwrap()),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Another build downloading the same archive finishes first, its download is then verified.
+    let mut lock_path = path.clone().into_os_string();
+    lock_path.push(".lock");
+    let _lock = FileLock::acquire(Path::new(&lock_path), DOWNLOAD_LOCK_TIMEOUT)?;
+
+    if path.exists() {
+        match sha256_file(&path)
+            .map_err(Into::into)
+            .and_then(|sha256| verify(&path, &sha256))
+        {
+            Ok(()) => {
+                info!("using cached {}", path.display());
+                // Marks it as used for the eviction of the least recently used archives.
+                let _ = fs::File::options()
+                    .append(true)
+                    .open(&path)
+                    .and_then(|file| file.set_modified(std::time::SystemTime::now()));
+                return Ok(path);
+            }
+            Err(e) => {
+                warning!("evicting corrupt cache entry {}: {}", path.display(), e);
+                fs::remove_file(&path)?;
+            }
+        }
+    }
+
+    let sha256 = download_file(url, &path)?;
+
+    if let Err(e) = verify(&path, &sha256) {
+        let _ = fs::remove_file(&path);
+        return Err(e);
+    }
+
+    if let Some((dir, limit)) = cache {
+        for evicted in evict_cache_entries(dir, limit, &path) {
+            info!("evicted {} from the download cache", evicted.display());
+        }
+    }
+    Ok(path)
+}
+
+/// Removes the least recently used archives of the cache in `dir` until it takes no more than
+/// `limit` bytes, except `keep` and the archives another build holds the lock of. Returns the
+/// removed archives.
+pub(crate) fn evict_cache_entries(dir: &Path, limit: u64, keep: &Path) -> Vec<PathBuf> {
+    let mut entries = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                dirs.push(entry.path());
+            } else {
+                let used = metadata.modified().unwrap_or(std::time::UNIX_EPOCH);
+                entries.push((used, metadata.len(), entry.path()));
+            }
+        }
+    }
+
+    let mut size: u64 = entries.iter().map(|(_, len, _)| len).sum();
+    entries.sort();
+    let mut evicted = Vec::new();
+    for (_, len, path) in entries {
+        if size <= limit {
+            break;
+        }
+        let mut lock_path = path.clone().into_os_string();
+        lock_path.push(".lock");
+        let in_use = matches!(
+            path.extension().and_then(|extension| extension.to_str()),
+            Some("lock" | "partial")
+        ) || Path::new(&lock_path).exists();
+        if path == keep || in_use {
+            continue;
+        }
+        if fs::remove_file(&path).is_ok() {
+            size -= len;
+            evicted.push(path);
+        }
+    }
+    evicted
+}
+
+pub(crate) fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hex(&hasher.finalize()))
+}
+
+pub(crate) fn verify_sha256(
+    path: &Path,
+    actual: &str,
+    expected: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let expected = match expected {
+        Some(expected) => expected,
+        None => {
+            warning!("skipping SHA-256 verification of {}", path.display());
+            return Ok(());
+        }
+    };
+
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "SHA-256 mismatch for {}\n  expected: {}\n  actual:   {}",
+            path.display(),
+            expected,
+            actual
+        )
+        .into())
+    }
+}
+
+/// Checks that `dir` looks like an assimp source tree, listing everything that is missing otherwise.
+fn validate_source_dir(dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let missing: Vec<String> = ["CMakeLists.txt", "include/assimp"]
+        .iter()
+        .filter(|entry| !dir.join(entry).exists())
+        .map(|entry| format!("  - {}", dir.join(entry).display()))
+        .collect();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} is not a valid assimp source directory, missing:\n{}",
+            dir.display(),
+            missing.join("\n")
+        )
+        .into())
+    }
+}
+
+/// Whether the git submodule at `dir` was checked out, as opposed to being absent or an empty
+/// directory left behind by a non-recursive clone.
+fn is_checked_out(dir: &Path) -> bool {
+    fs::metadata(dir.join("CMakeLists.txt"))
+        .map(|metadata| metadata.len() > 0)
+        .unwrap_or(false)
+}
+
+/// Checks that the source of `russimp-sys-src`, of `vendored_version` and extracted from the
+/// archive with `archive_sha256`, is that of `version`, and of the archive pinned for it.
+#[cfg_attr(not(feature = "vendored"), allow(dead_code))]
+pub(crate) fn check_vendored_source(
+    version: &str,
+    vendored_version: &str,
+    archive_sha256: &str,
+) -> Result<(), String> {
+    if vendored_version != version {
+        return Err(format!(
+            "The vendored source is assimp v{}, not v{}. Select the source with \
+            RUSSIMP_ASSIMP_SOURCE_DIR instead.",
+            vendored_version, version
+        ));
+    }
+    if version == ASSIMP_VERSION && !archive_sha256.eq_ignore_ascii_case(ASSIMP_SOURCE_SHA256) {
+        return Err(format!(
+            "The vendored assimp v{} source was extracted from an archive with SHA-256 {}, \
+            expected {}.",
+            version, archive_sha256, ASSIMP_SOURCE_SHA256
+        ));
+    }
+    Ok(())
+}
+
+/// The source of `russimp-sys-src` with the `vendored` feature, checked to be that of `version`.
+#[cfg(feature = "vendored")]
+fn vendored_source_dir(version: &str) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    check_vendored_source(
+        version,
+        russimp_sys_src::VERSION,
+        russimp_sys_src::ARCHIVE_SHA256,
+    )?;
+    let source_dir = russimp_sys_src::source_dir();
+    validate_source_dir(&source_dir)?;
+    info!(
+        "using assimp source from russimp-sys-src ({})",
+        source_dir.display()
+    );
+    Ok(Some(source_dir))
+}
+
+#[cfg(not(feature = "vendored"))]
+fn vendored_source_dir(_version: &str) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    Ok(None)
+}
+
+/// Unpacks a gzipped assimp source tarball into `out_dir/assimp`.
+///
+/// The archive is unpacked next to it first and moved into place once complete, so a failed
+/// extraction leaves nothing behind that passes for the source.
+pub(crate) fn extract_source_archive<R: io::Read>(
+    archive: R,
+    out_dir: &Path,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let assimp_dir = out_dir.join("assimp");
+    let staging = out_dir.join(format!("assimp.tmp-{}", std::process::id()));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+
+    let extracted = unpack_source_archive(archive, &staging).and_then(|root| {
+        validate_source_dir(&root)?;
+        if assimp_dir.exists() {
+            fs::remove_dir_all(&assimp_dir)?;
+        }
+        fs::rename(&root, &assimp_dir)?;
+        Ok(())
+    });
+    let _ = fs::remove_dir_all(&staging);
+    extracted.map(|()| assimp_dir)
+}
+
+/// Unpacks the tarball `archive` into `dest`, returning its single top-level directory, e.g.
+/// `assimp-5.4.3` of a tag archive or `assimp-assimp-<commit>` of a commit one.
+fn unpack_source_archive<R: io::Read>(
+    archive: R,
+    dest: &Path,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    extract_archive(archive, dest)?;
+
+    let dirs: Vec<PathBuf> = fs::read_dir(dest)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    match dirs.as_slice() {
+        [root] => Ok(root.clone()),
+        [] => Err("the archive contains no directory".into()),
+        _ => Err(format!(
+            "the archive contains several top-level directories: {}",
+            dir_listing(dest)
+        )
+        .into()),
+    }
+}
+
+/// How a tarball is compressed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Compression {
+    Gzip,
+    Zstd,
+}
+
+/// Unpacks the gzip or zstd compressed tarball `archive` into `dest`, for the source archives and
+/// the prebuilt packages alike. The decompressor is picked from the magic bytes rather than a file
+/// name. Entries ending up outside of `dest`, or links pointing there, fail the extraction.
+pub(crate) fn extract_archive<R: io::Read>(archive: R, dest: &Path) -> Result<Compression, String> {
+    use io::BufRead;
+
+    let mut archive = io::BufReader::new(archive);
+    let magic = archive.fill_buf().map_err(|e| e.to_string())?;
+    // Some servers answer with an HTML error page instead of a 404, don't feed that to a decoder.
+    let compression = match magic {
+        [0x1f, 0x8b, ..] => Compression::Gzip,
+        [0x28, 0xb5, 0x2f, 0xfd, ..] => Compression::Zstd,
+        [b'P', b'K', 0x03, 0x04, ..] => {
+            return Err(
+                "a zip archive, only gzip or zstd compressed tarballs are supported, \
+                 download the .tar.gz archive instead"
+                    .to_string(),
+            )
+        }
+        _ => {
+            return Err(format!(
+                "not a gzip or zstd compressed archive, it starts with {:?}",
+                String::from_utf8_lossy(&magic[..magic.len().min(64)])
+            ))
+        }
+    };
+    let decoder: Box<dyn io::Read> = match compression {
+        Compression::Gzip => Box::new(GzDecoder::new(archive)),
+        #[cfg(feature = "prebuilt")]
+        Compression::Zstd => Box::new(
+            ruzstd::decoding::StreamingDecoder::new(archive)
+                .map_err(|e| format!("invalid zstd archive: {}", e))?,
+        ),
+        #[cfg(not(feature = "prebuilt"))]
+        Compression::Zstd => {
+            return Err("zstd compressed archives require the `prebuilt` feature".to_string())
+        }
+    };
+
+    fs::create_dir_all(dest).map_err(|e| format!("{}: {}", dest.display(), e))?;
+    let mut archive = tar::Archive::new(decoder);
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        let path = entry.path().map_err(|e| e.to_string())?.into_owned();
+        let symlink = entry.header().entry_type().is_symlink();
+        if let Some(link) = entry.link_name().map_err(|e| e.to_string())? {
+            if !link_stays_inside(&path, &link, symlink) {
+                return Err(format!(
+                    "the link {} of the archive points outside of it, to {}",
+                    path.display(),
+                    link.display()
+                ));
+            }
+        }
+        if !entry
+            .unpack_in(dest)
+            .map_err(|e| format!("{}: {}", path.display(), e))?
+        {
+            return Err(format!(
+                "{} of the archive is outside of it",
+                path.display()
+            ));
+        }
+    }
+    Ok(compression)
+}
+
+/// Whether the link to `target` of the archive entry at `path` stays inside the archive. Symbolic
+/// links are relative to the directory of the entry, hard links to the root of the archive.
+pub(crate) fn link_stays_inside(path: &Path, target: &Path, symlink: bool) -> bool {
+    let mut depth = 0usize;
+    let parent = path.parent().filter(|_| symlink).unwrap_or(Path::new(""));
+    for component in parent.components().chain(target.components()) {
+        depth = match component {
+            Component::Normal(_) => depth + 1,
+            Component::CurDir => depth,
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(depth) => depth,
+                None => return false,
+            },
+            Component::RootDir | Component::Prefix(_) => return false,
+        };
+    }
+    true
+}
+
+/// Downloads, verifies and extracts the source archive of the assimp `version` into `out_dir/assimp`.
+fn download_source(
+    version: &str,
+    out_dir: &Path,
+    expected_sha256: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let source_url = assimp_source_url(version);
+
+    // The archive is verified before anything is extracted, so a corrupted or tampered
+    // download never leaves a (partially) extracted tree behind.
+    let archive_path = fetch_archive(
+        &source_url,
+        &Path::new("source").join(format!("assimp-{}.tar.gz", version)),
+        out_dir,
+        |path, sha256| verify_sha256(path, sha256, expected_sha256.as_deref()),
+    )?;
+
+    info!("extracting source archive");
+    if let Err(e) = extract_source_archive(fs::File::open(&archive_path)?, out_dir) {
+        // Don't keep failing on a cached archive that can't be extracted.
+        let _ = fs::remove_file(&archive_path);
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Shallow clones the `v<version>` tag of assimp into `dest` with the `git` binary.
+///
+/// Fallback for networks where the GitHub archive downloads are blocked but git still works.
+fn clone_source(version: &str, dest: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if dest.exists() {
+        fs::remove_dir_all(dest)?;
+    }
+
+    let status = std::process::Command::new("git")
+        .args(["-c", "advice.detachedHead=false", "clone", "--depth", "1"])
+        .arg("--branch")
+        .arg(format!("v{}", version))
+        .arg("https://github.com/assimp/assimp.git")
+        .arg(dest)
+        .status()
+        .map_err(|e| format!("unable to run git: {}", e))?;
+
+    if !status.success() {
+        let _ = fs::remove_dir_all(dest);
+        return Err(format!("git clone exited with {}", status).into());
+    }
+
+    validate_source_dir(dest)
+}
+
+/// This function ensures the assimp library is downloaded for building from source, which caused the static-link feature to break
+///
+/// The source is picked in this order:
+/// 1. `RUSSIMP_ASSIMP_SOURCE_DIR`, a pre-downloaded source tree used as-is.
+/// 2. The in-tree `assimp` git submodule, when it was checked out.
+/// 3. The source of `russimp-sys-src` with the `vendored` feature.
+/// 4. The downloaded source archive, extracted into `OUT_DIR`.
+///
+/// Reference: https://github.com/jkvargas/russimp-sys/issues/49
+pub(crate) fn ensure_submodules(version: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_ASSIMP_SOURCE_DIR");
+    if let Some(source_dir) = env::var_os("RUSSIMP_ASSIMP_SOURCE_DIR") {
+        let source_dir = PathBuf::from(source_dir);
+        validate_source_dir(&source_dir)?;
+        info!(
+            "using assimp source from RUSSIMP_ASSIMP_SOURCE_DIR ({})",
+            source_dir.display()
+        );
+        println!(
+            "cargo:rerun-if-changed={}",
+            source_dir.join("include").display()
+        );
+        return Ok(source_dir);
+    }
+
+    let submodule_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("assimp");
+    // The submodule is pinned to ASSIMP_VERSION, so any other version has to be fetched.
+    if version == ASSIMP_VERSION
+        && is_checked_out(&submodule_dir)
+        && validate_source_dir(&submodule_dir).is_ok()
+    {
+        info!(
+            "using assimp source from the git submodule ({})",
+            submodule_dir.display()
+        );
+        println!(
+            "cargo:rerun-if-changed={}",
+            submodule_dir.join("include").display()
+        );
+        return Ok(submodule_dir);
+    }
+
+    if let Some(source_dir) = vendored_source_dir(version)? {
+        return Ok(source_dir);
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let assimp_dir = out_dir.join("assimp");
+    let manifest_path = source_manifest_path(&out_dir);
+    info!("out_dir: {}", out_dir.display());
+
+    let expected_sha256 = assimp_source_sha256(version)?;
+    let manifest = source_manifest(version, expected_sha256.as_deref());
+    if source_is_stale(&assimp_dir, &manifest_path, &manifest) {
+        info!(
+            "assimp v{} source not found in OUT_DIR, fetching it",
+            version
+        );
+        // Written once the source is complete, an interrupted fetch is started over.
+        let _ = fs::remove_file(&manifest_path);
+
+        if assimp_dir.exists() {
+            std::fs::remove_dir_all(&assimp_dir)?;
+        }
+
+        if let Err(download_error) = download_source(version, &out_dir, expected_sha256) {
+            warning!(
+                "downloading the assimp source failed ({}), falling back to git clone",
+                download_error
+            );
+
+            if let Err(clone_error) = clone_source(version, &assimp_dir) {
+                return Err(format!(
+                    "Downloading the source archive failed: {}\nCloning the repository failed as well: {}",
+                    download_error, clone_error
+                )
+                .into());
+            }
+        }
+
+        fs::write(&manifest_path, &manifest)?;
+        info!("fetched the assimp v{} source", version);
+    }
+
+    info!("using assimp source from {}", assimp_dir.display());
+    Ok(assimp_dir)
+}
+
+/// The manifest written next to the assimp source fetched into `out_dir`.
+pub(crate) fn source_manifest_path(out_dir: &Path) -> PathBuf {
+    out_dir.join("assimp.source")
+}
+
+/// Identifies the assimp source fetched into OUT_DIR: its version, and the SHA-256 of the archive it
+/// was downloaded from. The git clone fallback checks out the same tag, and is recorded with the
+/// archive it stands in for.
+pub(crate) fn source_manifest(version: &str, sha256: Option<&str>) -> String {
+    let archive = sha256.map_or("unverified".to_string(), |sha256| {
+        format!("sha256:{}", sha256)
+    });
+    format!("version={}\narchive={}\n", version, archive)
+}
+
+/// Whether the source in `assimp_dir` has to be fetched again, as it is missing or its manifest
+/// isn't `manifest`, e.g. since an upgrade of the crate pinned another assimp.
+pub(crate) fn source_is_stale(assimp_dir: &Path, manifest_path: &Path, manifest: &str) -> bool {
+    if !assimp_dir.join("CMakeLists.txt").is_file() {
+        return true;
+    }
+    match fs::read_to_string(manifest_path) {
+        Ok(fetched) if fetched == manifest => false,
+        Ok(fetched) => {
+            info!(
+                "the assimp source in {} is stale ({}), fetching it again",
+                assimp_dir.display(),
+                fetched.trim().replace('\n', ", ")
+            );
+            true
+        }
+        Err(_) => true,
+    }
+}
+
+/// The source of `version` couldn't be fetched, nor a system assimp be found instead.
+pub(crate) fn source_error(
+    e: Box<dyn std::error::Error>,
+    version: &str,
+    system_probes: &[String],
+) -> BuildError {
+    BuildError::Download {
+        archive_name: format!("the assimp {} source", version),
+        url: assimp_source_url(version),
+        reason: format!(
+            "{}\n\nSystem assimp fallback: {}",
+            e,
+            if system_probes.is_empty() {
+                "disabled by RUSSIMP_NO_SYSTEM_FALLBACK".to_string()
+            } else {
+                system_probes
+                    .iter()
+                    .map(|probe| format!("\n- {}", probe))
+                    .collect()
+            }
+        ),
+    }
+}
@@ -0,0 +1,387 @@
+//! The `config_keys` and `material_keys` modules, generated from the defines of the assimp headers.
+
+/// A `#define` of an assimp header, e.g. `AI_CONFIG_PP_SBP_REMOVE` for `"PP_SBP_REMOVE"`, with
+/// the `params` of function-like ones and the lines of the doc comment before it.
+#[derive(Debug, PartialEq)]
+pub(crate) struct HeaderDefine {
+    pub(crate) name: String,
+    pub(crate) params: Option<Vec<String>>,
+    pub(crate) value: String,
+    pub(crate) doc: Vec<String>,
+}
+
+/// The `#define`s of `header` whose names start with one of `prefixes`, the first one of each
+/// name. The last doxygen comment before a define is its doc, or else the `//` comments right
+/// above it, unless other code is in between.
+pub(crate) fn header_defines(header: &str, prefixes: &[&str]) -> Vec<HeaderDefine> {
+    let mut defines: Vec<HeaderDefine> = Vec::new();
+    let mut doc: Option<Vec<String>> = None;
+    let mut line_doc: Vec<String> = Vec::new();
+    let mut comment: Option<Vec<String>> = None;
+    let mut lines = header.lines();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        let define = trimmed
+            .strip_prefix('#')
+            .map(str::trim_start)
+            .and_then(|define| define.strip_prefix("define"))
+            .filter(|define| define.starts_with(char::is_whitespace))
+            .map(str::trim_start)
+            .filter(|define| prefixes.iter().any(|prefix| define.starts_with(prefix)));
+        if let Some(lines) = comment.as_mut() {
+            let (text, end) = match trimmed.split_once("*/") {
+                Some((text, _)) => (text, true),
+                None => (trimmed, false),
+            };
+            lines.push(text.trim_start_matches('*').trim().to_string());
+            if end {
+                doc = comment.take();
+            }
+        } else if let Some(text) = trimmed.strip_prefix("/**") {
+            let text = text.trim_start_matches('<');
+            match text.split_once("*/") {
+                Some((text, _)) => doc = Some(vec![text.trim().to_string()]),
+                None => comment = Some(vec![text.trim().to_string()]),
+            }
+        } else if let Some(text) = trimmed.strip_prefix("//") {
+            // Rulers, headings above them and doxygen commands aren't part of the doc.
+            let text = text.trim();
+            if text.starts_with('!') || text.chars().all(|c| "-=#*/".contains(c)) {
+                line_doc.clear();
+            } else {
+                line_doc.push(text.to_string());
+            }
+        } else if let Some(define) = define {
+            let mut define = define.to_string();
+            while define.ends_with('\\') {
+                define.pop();
+                define.push(' ');
+                define.push_str(lines.next().unwrap_or_default().trim());
+            }
+            let name_len = define
+                .find(|c: char| c.is_whitespace() || c == '(')
+                .unwrap_or(define.len());
+            let (name, mut value) = define.split_at(name_len);
+            let mut params = None;
+            if let Some((list, rest)) = value
+                .strip_prefix('(')
+                .and_then(|value| value.split_once(')'))
+            {
+                params = Some(
+                    list.split(',')
+                        .map(|param| param.trim().to_string())
+                        .collect(),
+                );
+                value = rest;
+            }
+            let doc = doc.take().unwrap_or_else(|| line_doc.clone());
+            line_doc.clear();
+            if !defines.iter().any(|define| define.name == name) {
+                defines.push(HeaderDefine {
+                    name: name.to_string(),
+                    params,
+                    value: value.trim().to_string(),
+                    doc,
+                });
+            }
+        } else if !trimmed.is_empty() {
+            doc = None;
+            line_doc.clear();
+        }
+    }
+    for define in &mut defines {
+        let doc: Vec<String> = define
+            .doc
+            .iter()
+            .map(|line| {
+                let line = line.strip_prefix("@brief").unwrap_or(line).trim();
+                line.to_string()
+            })
+            .collect();
+        // Without the blank lines around the comment.
+        let start = doc
+            .iter()
+            .position(|line| !line.is_empty())
+            .unwrap_or(doc.len());
+        let end = doc
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(start, |end| end + 1);
+        define.doc = doc[start..end].to_vec();
+    }
+    defines
+}
+
+/// The text of a C string literal, without the quotes, unless it needs unescaping.
+fn string_literal(value: &str) -> Option<&str> {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .filter(|value| !value.contains(['"', '\\']))
+}
+
+/// The `AI_CONFIG_*` keys of the `config.h` in `header`, which bindgen only generates as byte
+/// arrays, with the text of their string literals as the value.
+pub(crate) fn config_keys(header: &str) -> Vec<HeaderDefine> {
+    header_defines(header, &["AI_CONFIG_"])
+        .into_iter()
+        .filter(|define| define.params.is_none())
+        .filter_map(|define| {
+            let value = string_literal(&define.value)?.to_string();
+            Some(HeaderDefine { value, ..define })
+        })
+        .collect()
+}
+
+/// The doc comment of a generated item, its lines are trimmed, rustdoc would take indented ones
+/// for doctests.
+fn doc_comment(doc: &[String], indent: &str) -> String {
+    doc.iter()
+        .map(|line| {
+            if line.is_empty() {
+                format!("{}///\n", indent)
+            } else {
+                format!("{}/// {}\n", indent, line)
+            }
+        })
+        .collect()
+}
+
+/// A `&'static CStr` literal of the string `value`.
+fn c_str(value: &str) -> String {
+    format!("c\"{}\"", value)
+}
+
+/// The `config_keys` module of the crate, a `&CStr` for each of the `keys`.
+pub(crate) fn config_keys_module(keys: &[HeaderDefine]) -> String {
+    keys.iter()
+        .map(|key| {
+            format!(
+                "{}pub const {}: &std::ffi::CStr = {};\n",
+                doc_comment(&key.doc, ""),
+                key.name,
+                c_str(&key.value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The `material_keys` module of the crate, the `AI_MATKEY_*` macros of `assimp/material.h` in
+/// `core` and the glTF-specific ones of `assimp/GltfMaterial.h` and `assimp/pbrmaterial.h` in
+/// `gltf`, which go to a `gltf` module inside it. A `"key", type, index` macro becomes a
+/// `MaterialKey`, a `type, index` one a `TextureSlot` and the function-like ones const fns of
+/// the texture type and index. Aliases of other macros get their type, macros the generator
+/// can't translate are left out.
+pub(crate) fn material_keys_module(core: &[HeaderDefine], gltf: &[HeaderDefine]) -> String {
+    use std::collections::HashMap;
+
+    /// The `#define`s of a module, its path and the translated ones by index.
+    struct Module<'a> {
+        defines: &'a [HeaderDefine],
+        path: &'static str,
+        items: Vec<Option<String>>,
+    }
+
+    /// A translated macro, the type of the item and the parameter count if it's a function.
+    #[derive(Clone, Copy)]
+    struct Item {
+        ty: &'static str,
+        params: Option<usize>,
+    }
+
+    // Splits at the commas outside of parentheses and string literals.
+    fn split_args(value: &str) -> Vec<&str> {
+        let (mut args, mut depth, mut quoted, mut start) = (Vec::new(), 0, false, 0);
+        for (i, c) in value.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                '(' if !quoted => depth += 1,
+                ')' if !quoted => depth -= 1,
+                ',' if !quoted && depth == 0 => {
+                    args.push(value[start..i].trim());
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        args.push(value[start..].trim());
+        args
+    }
+
+    fn is_ident(value: &str) -> bool {
+        value.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    // The parameters are the texture type and index by their upstream names.
+    fn param(name: &str) -> Option<&'static str> {
+        match name {
+            "type" => Some("texture_type"),
+            "N" => Some("index"),
+            _ => None,
+        }
+    }
+
+    // A texture type or index argument.
+    fn number(arg: &str, params: &[String]) -> Option<String> {
+        if params.iter().any(|param| param == arg) {
+            return param(arg).map(str::to_string);
+        }
+        if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_digit()) {
+            return Some(arg.to_string());
+        }
+        arg.strip_prefix("aiTextureType_")
+            .filter(|name| is_ident(name))
+            .map(|name| format!("crate::aiTextureType_aiTextureType_{} as _", name))
+    }
+
+    fn field(name: &str, value: String) -> String {
+        if name == value {
+            value
+        } else {
+            format!("{}: {}", name, value)
+        }
+    }
+
+    let bases: HashMap<&str, &str> = core
+        .iter()
+        .chain(gltf)
+        .filter(|define| define.params.is_none())
+        .filter_map(|define| Some((define.name.as_str(), string_literal(&define.value)?)))
+        .collect();
+    let mut modules = [
+        Module {
+            defines: core,
+            path: "crate::material_keys",
+            items: vec![None; core.len()],
+        },
+        Module {
+            defines: gltf,
+            path: "crate::material_keys::gltf",
+            items: vec![None; gltf.len()],
+        },
+    ];
+    let mut translated: HashMap<String, (String, Item)> = HashMap::new();
+    // Until no alias resolves to a macro translated in the pass before.
+    loop {
+        let mut progress = false;
+        for module in &mut modules {
+            for (define, item) in module.defines.iter().zip(&mut module.items) {
+                if item.is_some() || !define.name.starts_with("AI_MATKEY_") {
+                    continue;
+                }
+                let params: &[String] = define.params.as_deref().unwrap_or_default();
+                if params.iter().any(|name| param(name).is_none()) {
+                    continue;
+                }
+                let args = split_args(&define.value);
+                let body = match args[..] {
+                    [key, texture_type, index] => {
+                        let key = string_literal(key).or_else(|| bases.get(key).copied());
+                        key.zip(number(texture_type, params))
+                            .zip(number(index, params))
+                            .map(|((key, texture_type), index)| {
+                                let body = format!(
+                                    "crate::MaterialKey {{\n    key: {},\n    {},\n    {},\n}}",
+                                    c_str(key),
+                                    field("texture_type", texture_type),
+                                    field("index", index)
+                                );
+                                (body, "crate::MaterialKey")
+                            })
+                    }
+                    [texture_type, index] => number(texture_type, params)
+                        .zip(number(index, params))
+                        .map(|(texture_type, index)| {
+                            let body = format!(
+                                "crate::TextureSlot {{\n    {},\n    {},\n}}",
+                                field("texture_type", texture_type),
+                                field("index", index)
+                            );
+                            (body, "crate::TextureSlot")
+                        }),
+                    [alias] => {
+                        let (name, call) = match alias.split_once('(') {
+                            Some((name, args)) => (name.trim(), args.strip_suffix(')')),
+                            None => (alias, None),
+                        };
+                        let call_args: Option<Vec<String>> = call.map(|args| {
+                            split_args(args)
+                                .into_iter()
+                                .map_while(|arg| number(arg, params))
+                                .collect()
+                        });
+                        translated.get(name).and_then(|(path, target)| {
+                            let ty = target.ty;
+                            match (target.params, call_args) {
+                                (None, None) => Some((path.clone(), ty)),
+                                (Some(count), Some(args)) if args.len() == count => {
+                                    Some((format!("{}({})", path, args.join(", ")), ty))
+                                }
+                                _ => None,
+                            }
+                        })
+                    }
+                    _ => None,
+                };
+                let Some((body, ty)) = body else {
+                    continue;
+                };
+                let doc = doc_comment(&define.doc, "");
+                *item = Some(match &define.params {
+                    Some(params) => {
+                        let params: Vec<String> = params
+                            .iter()
+                            .filter_map(|name| param(name))
+                            .map(|name| format!("{}: u32", name))
+                            .collect();
+                        format!(
+                            "{}pub const fn {}({}) -> {} {{\n{}\n}}\n",
+                            doc,
+                            define.name,
+                            params.join(", "),
+                            ty,
+                            body.lines()
+                                .map(|line| format!("    {}", line))
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        )
+                    }
+                    None => format!("{}pub const {}: {} = {};\n", doc, define.name, ty, body),
+                });
+                let info = Item {
+                    ty,
+                    params: define.params.as_ref().map(Vec::len),
+                };
+                translated.insert(
+                    define.name.clone(),
+                    (format!("{}::{}", module.path, define.name), info),
+                );
+                progress = true;
+            }
+        }
+        if !progress {
+            break;
+        }
+    }
+    let [core, gltf] = modules.map(|module| module.items.into_iter().flatten().collect::<Vec<_>>());
+    let gltf = gltf
+        .join("\n")
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("    {}", line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "{}\n/// The glTF-specific keys of `assimp/GltfMaterial.h` and the aliases of the deprecated \
+        `assimp/pbrmaterial.h`.\npub mod gltf {{\n{}\n}}\n",
+        core.join("\n"),
+        gltf
+    )
+}
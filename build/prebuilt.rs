@@ -0,0 +1,512 @@
+//! The prebuilt packages of assimp: their pins, signatures, and the libraries they link.
+
+use super::*;
+
+/// The release the prebuilt packages are downloaded from, which is also part of their names.
+///
+/// This only changes when new packages are published, not with every release of the crate. Can be
+/// overridden with the `RUSSIMP_PREBUILT_VERSION` environment variable.
+const PREBUILT_PACKAGE_VERSION: &str = "2.0.3";
+
+pub(crate) fn prebuilt_package_version() -> String {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_PREBUILT_VERSION");
+
+    match env::var("RUSSIMP_PREBUILT_VERSION") {
+        Ok(version) if !version.trim().is_empty() => {
+            version.trim().trim_start_matches('v').to_string()
+        }
+        _ => PREBUILT_PACKAGE_VERSION.to_string(),
+    }
+}
+
+/// The GitHub release the packages of `version` are attached to, published by the prebuilt workflow
+/// apart from the releases of the crate.
+pub(crate) fn prebuilt_release_url(version: &str) -> String {
+    format!(
+        "https://github.com/jkvargas/russimp-sys/releases/download/prebuilt-v{}",
+        version
+    )
+}
+
+/// The targets prebuilt packages are published for by the release workflow.
+const PREBUILT_TARGETS: &[&str] = &[
+    "x86_64-unknown-linux-gnu",
+    "aarch64-unknown-linux-gnu",
+    "x86_64-apple-darwin",
+    "aarch64-apple-darwin",
+    UNIVERSAL_APPLE_DARWIN,
+    "x86_64-pc-windows-msvc",
+    "aarch64-pc-windows-msvc",
+    "x86_64-pc-windows-gnu",
+];
+
+/// The names of the prebuilt archives that can be linked for `target`, most specific first. The
+/// faster to extract `.tar.zst` archives are preferred over `.tar.gz` ones.
+///
+/// Different C libraries aren't interchangeable, so e.g. `-linux-gnu` and `-linux-musl` packages are
+/// never substituted for each other; only spellings of the same target are.
+pub(crate) fn candidate_archive_names(target: &str, linkage: &str, version: &str) -> Vec<String> {
+    let mut targets = vec![target.to_string()];
+
+    // Apple's toolchain calls the architecture arm64.
+    if let Some(os) = target.strip_prefix("aarch64-apple-") {
+        targets.push(format!("arm64-apple-{}", os));
+    }
+    // As do the Windows SDK and Visual Studio.
+    if let Some(env) = target.strip_prefix("aarch64-pc-windows-") {
+        targets.push(format!("arm64-pc-windows-{}", env));
+    }
+    // GNU toolchains commonly leave out the vendor.
+    if let Some((arch, env)) = target.split_once("-unknown-linux-") {
+        targets.push(format!("{}-linux-{}", arch, env));
+    }
+    // Universal packages link on either Mac architecture.
+    if target.ends_with("-apple-darwin") {
+        targets.push(UNIVERSAL_APPLE_DARWIN.to_string());
+    }
+
+    // Static MSVC packages were built against the static C runtime only before it was named.
+    let linkages = if linkage == "static-mt" {
+        vec![linkage, "static"]
+    } else {
+        vec![linkage]
+    };
+
+    linkages
+        .iter()
+        .flat_map(|linkage| {
+            targets.iter().flat_map(move |target| {
+                ["tar.zst", "tar.gz"].map(|extension| {
+                    format!("russimp-{}-{}-{}.{}", version, target, linkage, extension)
+                })
+            })
+        })
+        .collect()
+}
+
+/// The checksum file pinning the prebuilt archives of PREBUILT_PACKAGE_VERSION, in `sha256sum`
+/// format.
+///
+/// It is committed from the `prebuilt.sha256` the prebuilt workflow publishes with the packages,
+/// and the release workflow fails when it doesn't match that one.
+const PREBUILT_SHA256_FILE: &str = "prebuilt.sha256";
+
+/// The SHA-256 the prebuilt archive has to match, or `None` when verification was explicitly
+/// disabled by setting `RUSSIMP_PREBUILT_SHA256=skip`.
+fn prebuilt_sha256(archive_name: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-env-changed=RUSSIMP_PREBUILT_SHA256");
+
+    match env::var("RUSSIMP_PREBUILT_SHA256") {
+        Ok(sha) if sha.trim().eq_ignore_ascii_case("skip") => return Ok(None),
+        Ok(sha) if !sha.trim().is_empty() => return Ok(Some(sha.trim().to_ascii_lowercase())),
+        _ => {}
+    }
+
+    let sums_path =
+        PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join(PREBUILT_SHA256_FILE);
+    println!("cargo:rerun-if-changed={}", sums_path.display());
+    let sums = fs::read_to_string(&sums_path).unwrap_or_default();
+
+    sums.lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, name)| name.trim().trim_start_matches('*') == archive_name)
+        .map(|(sha, _)| Some(sha.to_ascii_lowercase()))
+        .ok_or_else(|| {
+            format!(
+                "No pinned SHA-256 for the prebuilt archive {} in {}. \
+                Set RUSSIMP_PREBUILT_SHA256 to the expected digest, or to \"skip\" to disable verification.",
+                archive_name,
+                sums_path.display()
+            )
+            .into()
+        })
+}
+
+/// The directory `RUSSIMP_PACKAGE_DIR` names, with a leading `~` expanded to `home` and relative
+/// to `manifest_dir` otherwise, which is the directory build scripts run in.
+pub(crate) fn resolve_package_dir(
+    dir: &str,
+    manifest_dir: &Path,
+    home: Option<&Path>,
+) -> Result<PathBuf, String> {
+    let dir = dir.trim();
+    let expanded = match dir.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            let home = home.ok_or_else(|| {
+                format!(
+                    "RUSSIMP_PACKAGE_DIR is {}, but the home directory is unknown",
+                    dir
+                )
+            })?;
+            home.join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(dir),
+    };
+    let resolved = manifest_dir.join(expanded);
+    let resolved = fs::canonicalize(&resolved).map_err(|e| {
+        format!(
+            "RUSSIMP_PACKAGE_DIR is {}, {} is unusable: {}",
+            dir,
+            resolved.display(),
+            e
+        )
+    })?;
+    if !resolved.is_dir() {
+        return Err(format!(
+            "RUSSIMP_PACKAGE_DIR is {}, but {} is not a directory",
+            dir,
+            resolved.display()
+        ));
+    }
+    Ok(resolved)
+}
+
+/// The names of the entries in `dir`, sorted, for error messages.
+pub(crate) fn dir_listing(dir: &Path) -> String {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    if names.is_empty() {
+        return "nothing".to_string();
+    }
+    names.sort();
+    names.join(", ")
+}
+
+/// Links the prebuilt package for the target, returning the include directory with the headers
+/// to generate the bindings from. The package has the headers and the license, the assimp source
+/// is only fetched to be built instead when no package was published for the target.
+pub(crate) fn link_from_package(
+    target: &TargetInfo,
+    assimp_version: &str,
+) -> Result<PathBuf, BuildError> {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let package_version = prebuilt_package_version();
+    let candidates =
+        candidate_archive_names(&target.triple, &prebuilt_linkage(target), &package_version);
+    // Looked up once the archive is at hand, so a missing package is reported as such.
+    let verify = |archive_name: &str, path: &Path, sha256: &str| {
+        verify_sha256(path, sha256, prebuilt_sha256(archive_name)?.as_deref())
+    };
+
+    println!("cargo:rerun-if-env-changed=RUSSIMP_PACKAGE_DIR");
+    let package_dir = match env::var("RUSSIMP_PACKAGE_DIR") {
+        Ok(dir) if !dir.trim().is_empty() => {
+            let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+            let home = env::var_os("HOME")
+                .or_else(|| env::var_os("USERPROFILE"))
+                .map(PathBuf::from);
+            let dir =
+                resolve_package_dir(&dir, &manifest_dir, home.as_deref()).map_err(|reason| {
+                    BuildError::Validation {
+                        reason,
+                        hint: "Set RUSSIMP_PACKAGE_DIR to a directory with packages created by \
+                            `cargo run --bin package`."
+                            .to_string(),
+                    }
+                })?;
+            Some(dir)
+        }
+        _ => None,
+    };
+    let local_package = package_dir.is_some();
+    let location = if let Some(dir) = &package_dir {
+        dir.display().to_string()
+    } else {
+        println!("cargo:rerun-if-env-changed=RUSSIMP_PREBUILT_URL");
+        match env::var("RUSSIMP_PREBUILT_URL") {
+            Ok(base_url) if !base_url.trim().is_empty() => {
+                base_url.trim().trim_end_matches('/').to_string()
+            }
+            _ => prebuilt_release_url(&package_version),
+        }
+    };
+
+    let mut found = None;
+    for archive_name in &candidates {
+        if local_package {
+            let archive_path = Path::new(&location).join(archive_name);
+            if !archive_path.is_file() {
+                continue;
+            }
+            println!("cargo:rerun-if-changed={}", archive_path.display());
+            sha256_file(&archive_path)
+                .map_err(|e| e.into())
+                .and_then(|sha256| verify(archive_name, &archive_path, &sha256))
+                .map_err(|e| BuildError::Extract {
+                    archive_name: archive_name.clone(),
+                    path: archive_path.clone(),
+                    reason: e.to_string(),
+                })?;
+            found = Some((archive_name.clone(), archive_path));
+            break;
+        }
+
+        let dl_link = format!("{}/{}", location, archive_name);
+        let fetched = fetch_archive(
+            &dl_link,
+            &Path::new("prebuilt").join(archive_name),
+            &out_dir,
+            |path, sha256| verify(archive_name, path, sha256),
+        );
+        match fetched {
+            Ok(archive_path) => {
+                found = Some((archive_name.clone(), archive_path));
+                break;
+            }
+            Err(e) if e.is::<NotFound>() => info!("{} was not found", dl_link),
+            Err(e) => {
+                return Err(BuildError::Download {
+                    archive_name: archive_name.clone(),
+                    url: dl_link,
+                    reason: e.to_string(),
+                })
+            }
+        }
+    }
+
+    let (archive_name, archive_path) = match found {
+        Some(found) => found,
+        None => {
+            let missing_error = |reason: String| BuildError::Download {
+                archive_name: candidates[0].clone(),
+                url: location.clone(),
+                reason,
+            };
+            let missing = format!(
+                "No prebuilt package exists for {} in {}, tried {}. Prebuilt packages are published for: {}",
+                target.triple,
+                location,
+                candidates.join(", "),
+                PREBUILT_TARGETS.join(", ")
+            );
+            // A package selected explicitly is a mistake to point out, rather than to build around.
+            if let Some(dir) = &package_dir {
+                return Err(missing_error(format!(
+                    "RUSSIMP_PACKAGE_DIR contains none of {} for {}, only: {}. Create the package with \
+                    `cargo run --bin package` for this target.",
+                    candidates.join(", "),
+                    target.triple,
+                    dir_listing(dir)
+                )));
+            }
+
+            println!("cargo:rerun-if-env-changed=RUSSIMP_NO_SOURCE_FALLBACK");
+            if env::var_os("RUSSIMP_NO_SOURCE_FALLBACK").is_some() {
+                return Err(missing_error(missing));
+            }
+            if let Some(toolchain) = missing_source_toolchain() {
+                return Err(missing_error(format!(
+                    "{}\nBuilding assimp from source instead is not possible either: {}",
+                    missing, toolchain
+                )));
+            }
+
+            warning!("{}; building assimp from source instead", missing);
+            let assimp_src_dir =
+                ensure_submodules(assimp_version).map_err(|e| BuildError::Download {
+                    archive_name: format!("the assimp {} source", assimp_version),
+                    url: assimp_source_url(assimp_version),
+                    reason: format!("{}\n\nIt was to be built as {}", e, missing),
+                })?;
+            build_from_source(target, assimp_version, &assimp_src_dir, None)?;
+            return Ok(assimp_src_dir.join("include"));
+        }
+    };
+    let archive_error = |path: &Path, reason: String| BuildError::Extract {
+        archive_name: archive_name.clone(),
+        path: path.to_path_buf(),
+        reason,
+    };
+
+    #[cfg(feature = "verify-signatures")]
+    verify_package_signature(&archive_path, &location, &archive_name, local_package)
+        .map_err(|reason| archive_error(&archive_path, reason))?;
+
+    info!(
+        "linking the prebuilt assimp from {}",
+        archive_path.display()
+    );
+
+    let ar_dest_dir = assimp_install_dir(&out_dir, target)?;
+    // Records which archive was extracted, so unchanged packages aren't unpacked on every build.
+    let marker_path = ar_dest_dir.join(".russimp-package");
+    let sha256 =
+        sha256_file(&archive_path).map_err(|e| archive_error(&archive_path, e.to_string()))?;
+    let marker = format!("{}\n{}\n", archive_name, sha256);
+
+    if fs::read_to_string(&marker_path).is_ok_and(|extracted| extracted == marker) {
+        info!("{} is already extracted", archive_name);
+    } else {
+        // Start from scratch, an earlier extraction may be stale or incomplete.
+        if ar_dest_dir.exists() {
+            fs::remove_dir_all(&ar_dest_dir)
+                .map_err(|e| archive_error(&archive_path, e.to_string()))?;
+        }
+
+        if let Err(reason) = unpack_package(&archive_path, &ar_dest_dir) {
+            if !local_package {
+                // Evict the downloaded archive so the next build fetches it again.
+                let _ = fs::remove_file(&archive_path);
+            }
+            return Err(archive_error(&archive_path, reason));
+        }
+
+        if let Err((dir, reason)) = validate_package(target, &ar_dest_dir) {
+            return Err(BuildError::Extract {
+                archive_name,
+                path: dir,
+                reason,
+            });
+        }
+
+        fs::write(&marker_path, marker).map_err(|e| archive_error(&archive_path, e.to_string()))?;
+    }
+
+    emit_link_search(&ar_dest_dir.join("lib"));
+
+    emit_link_search(&ar_dest_dir.join("bin"));
+    Ok(ar_dest_dir.join("include"))
+}
+
+/// Checks that an extracted package contains the headers and the library to link, returning the
+/// offending directory and what is wrong with it otherwise.
+pub(crate) fn validate_package(target: &TargetInfo, dir: &Path) -> Result<(), (PathBuf, String)> {
+    for subdir in ["include", "lib"] {
+        if !dir.join(subdir).is_dir() {
+            return Err((dir.join(subdir), "the directory is missing".to_string()));
+        }
+    }
+
+    // Catch a package built for another linkage here rather than as a linker error later on.
+    for (subdir, pattern) in assimp_library(target, false).file_patterns(target) {
+        let subdir = dir.join(subdir);
+        let files: Vec<String> = fs::read_dir(&subdir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if !files.iter().any(|file| matches_pattern(file, &pattern)) {
+            return Err((
+                subdir,
+                format!(
+                    "expected a file matching {}, found [{}]",
+                    pattern,
+                    files.join(", ")
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The minisign public key the prebuilt packages are signed with, shipped with the crate.
+#[cfg(feature = "verify-signatures")]
+const PREBUILT_PUBLIC_KEY_FILE: &str = "minisign.pub";
+
+/// Verifies the minisign signature published next to the package in `location` against the public
+/// key shipped with the crate. Local packages can opt out with `RUSSIMP_SKIP_SIGNATURE_CHECK`.
+#[cfg(feature = "verify-signatures")]
+fn verify_package_signature(
+    archive_path: &Path,
+    location: &str,
+    archive_name: &str,
+    local_package: bool,
+) -> Result<(), String> {
+    let signature_name = format!("{}.minisig", archive_name);
+    let signature_path = if local_package {
+        println!("cargo:rerun-if-env-changed=RUSSIMP_SKIP_SIGNATURE_CHECK");
+        if env::var_os("RUSSIMP_SKIP_SIGNATURE_CHECK").is_some() {
+            warning!(
+                "skipping the signature verification of {}",
+                archive_path.display()
+            );
+            return Ok(());
+        }
+        let signature_path = Path::new(location).join(&signature_name);
+        println!("cargo:rerun-if-changed={}", signature_path.display());
+        signature_path
+    } else {
+        let signature_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join(&signature_name);
+        download_file(&format!("{}/{}", location, signature_name), &signature_path)
+            .map_err(|e| e.to_string())?;
+        signature_path
+    };
+
+    let key_path =
+        PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join(PREBUILT_PUBLIC_KEY_FILE);
+    println!("cargo:rerun-if-changed={}", key_path.display());
+    let public_key = fs::read_to_string(&key_path).map_err(|e| {
+        format!(
+            "Unable to read the public key {}: {}",
+            key_path.display(),
+            e
+        )
+    })?;
+
+    verify_signature(archive_path, &signature_path, &public_key)
+}
+
+/// Verifies `archive_path` against the minisign signature at `signature_path`. `public_key` is
+/// either the contents of a `minisign.pub` file or just the base64 key.
+#[cfg(feature = "verify-signatures")]
+pub(crate) fn verify_signature(
+    archive_path: &Path,
+    signature_path: &Path,
+    public_key: &str,
+) -> Result<(), String> {
+    let fingerprint = public_key.lines().last().unwrap_or_default().trim();
+    let failed = |reason: String| {
+        format!(
+            "Signature verification of {} with the key {} failed: {}",
+            archive_path.display(),
+            fingerprint,
+            reason
+        )
+    };
+
+    let public_key =
+        minisign_verify::PublicKey::from_base64(fingerprint).map_err(|e| failed(e.to_string()))?;
+    let signature = minisign_verify::Signature::from_file(signature_path)
+        .map_err(|e| failed(format!("{}: {}", signature_path.display(), e)))?;
+    let data = fs::read(archive_path).map_err(|e| failed(e.to_string()))?;
+    // Only the prehashed signatures of current minisign versions are accepted.
+    public_key
+        .verify(&data, &signature, false)
+        .map_err(|e| failed(e.to_string()))
+}
+
+/// Unpacks the prebuilt package at `archive_path` into `dest`.
+pub(crate) fn unpack_package(archive_path: &Path, dest: &Path) -> Result<(), String> {
+    let file = fs::File::open(archive_path).map_err(|e| e.to_string())?;
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    let start = std::time::Instant::now();
+    let zstd = extract_archive(file, dest)? == Compression::Zstd;
+    if zstd != archive_path.to_string_lossy().ends_with(".tar.zst") {
+        warning!(
+            "{} is a {} compressed archive despite its name",
+            archive_path.display(),
+            if zstd { "zstd" } else { "gzip" }
+        );
+    }
+
+    info!(
+        "extracted {} ({:.1} MiB, {}) in {:.1}s",
+        archive_path.display(),
+        size as f64 / (1024.0 * 1024.0),
+        if zstd { "zstd" } else { "gzip" },
+        start.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
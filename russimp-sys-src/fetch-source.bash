@@ -0,0 +1,16 @@
+#!/usr/bin/env bash
+# Extracts the assimp source into assimp/ before the crate is packaged, from the tarball of the tag
+# of VERSION, checked against ARCHIVE_SHA256.
+set -euo pipefail
+cd "$(dirname "$0")"
+
+version=$(sed -n 's/^pub const VERSION: &str = "\(.*\)";/\1/p' src/lib.rs)
+sha256=$(sed -n 's/^pub const ARCHIVE_SHA256: &str = "\(.*\)";/\1/p' src/lib.rs)
+archive=$(mktemp)
+trap 'rm -f "$archive"' EXIT
+
+curl -fsSL "https://github.com/assimp/assimp/archive/refs/tags/v$version.tar.gz" -o "$archive"
+echo "$sha256  $archive" | sha256sum -c -
+rm -rf assimp
+mkdir assimp
+tar -xzf "$archive" -C assimp --strip-components=1
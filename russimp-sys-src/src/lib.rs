@@ -0,0 +1,16 @@
+//! The assimp source `russimp-sys` builds assimp from with its `vendored` feature, instead of
+//! downloading it. It is the GitHub source tarball of the `v{VERSION}` tag, without the tests,
+//! samples and documentation of assimp.
+
+use std::path::PathBuf;
+
+/// The version of assimp the source is of.
+pub const VERSION: &str = "5.4.3";
+
+/// SHA-256 of the GitHub source tarball the source was extracted from.
+pub const ARCHIVE_SHA256: &str = "66dfbaee288f2bc43172440a55d0235dfc7bf885dda6435c038e8000e79582cb";
+
+/// The directory of the source, with its `CMakeLists.txt`.
+pub fn source_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assimp")
+}
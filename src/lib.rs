@@ -3,12 +3,62 @@
 #![allow(non_snake_case)]
 #![allow(improper_ctypes)]
 
+// Links the zlib assimp is built against, nothing of it is used from Rust.
+#[cfg(any(feature = "libz-sys", feature = "zlib-ng"))]
+extern crate libz_sys as _;
+
 pub mod built_info {
     include!(concat!(env!("OUT_DIR"), "/built.rs"));
 }
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
+/// The property keys of `assimp/config.h` for `aiSetImportPropertyInteger` and the other
+/// `aiSetImportProperty*` functions, e.g. `AI_CONFIG_PP_SBP_REMOVE`, with their upstream docs.
+pub mod config_keys {
+    include!(concat!(env!("OUT_DIR"), "/config_keys.rs"));
+}
+
+/// A material property of `assimp/material.h` as the `aiGetMaterial*` functions take it, the
+/// key, texture type and texture index an `AI_MATKEY_*` macro expands to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaterialKey {
+    pub key: &'static std::ffi::CStr,
+    pub texture_type: u32,
+    pub index: u32,
+}
+
+/// A texture of a material, the texture type and index an `AI_MATKEY_*_TEXTURE` macro like
+/// `AI_MATKEY_BASE_COLOR_TEXTURE` expands to, e.g. for `aiGetMaterialTexture`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureSlot {
+    pub texture_type: u32,
+    pub index: u32,
+}
+
+/// The `AI_MATKEY_*` macros of `assimp/material.h`, e.g. `AI_MATKEY_BASE_COLOR`, as
+/// `MaterialKey`s and `TextureSlot`s, and the ones taking a texture type and index as const fns,
+/// e.g. `AI_MATKEY_TEXTURE(aiTextureType_aiTextureType_DIFFUSE as _, 0)`. The glTF-specific ones
+/// are in `gltf`.
+pub mod material_keys {
+    include!(concat!(env!("OUT_DIR"), "/material_keys.rs"));
+}
+
+/// The license of the linked assimp library, which has to be redistributed with it.
+///
+/// Build scripts of dependent crates find the same text at the path in `DEP_ASSIMP_LICENSE_PATH`.
+pub fn assimp_license() -> &'static str {
+    include_str!(concat!(env!("OUT_DIR"), "/LICENSE"))
+}
+
+/// Whether assimp was built without its exporters by the `no-export` feature, also told as
+/// `cfg(assimp_no_export)`.
+///
+/// The `aiExport*` functions are still declared, but calling them fails to link then, so wrappers
+/// gate their export APIs on this. Build scripts of dependent crates find it as
+/// `DEP_ASSIMP_NO_EXPORT=1`.
+pub const ASSIMP_NO_EXPORT: bool = cfg!(assimp_no_export);
+
 impl From<aiString> for String {
     fn from(string: aiString) -> Self {
         unsafe {
@@ -39,9 +89,641 @@ impl From<&aiString> for String {
 mod tests {
     use super::*;
 
+    /// Whether the importer of `extension` was left out by the `format-*` features, the tests
+    /// importing such files skip themselves then.
+    fn without_importer(extension: &std::ffi::CStr) -> bool {
+        unsafe { aiIsExtensionSupported(extension.as_ptr()) == 0 }
+    }
+
     /// A simple test to make sure assimp library linked properly.
     #[test]
     fn test_version() {
         let _ = unsafe { aiGetVersionMajor() };
     }
+
+    /// The version and compile flags of the library match the features it was built with.
+    #[test]
+    fn test_version_api() {
+        let (major, flags) = unsafe {
+            assert!(!aiGetLegalString().is_null());
+            assert!(!aiGetBranchName().is_null());
+            let _ = (
+                aiGetVersionMinor(),
+                aiGetVersionPatch(),
+                aiGetVersionRevision(),
+            );
+            (aiGetVersionMajor(), aiGetCompileFlags())
+        };
+        assert!(major >= 5, "assimp {}", major);
+        if cfg!(feature = "static-link") {
+            assert_eq!(flags & ASSIMP_CFLAGS_SHARED, 0);
+        }
+        assert_eq!(
+            flags & ASSIMP_CFLAGS_DOUBLE_SUPPORT != 0,
+            cfg!(feature = "double-precision")
+        );
+    }
+
+    /// The bindings have to agree with the library on the size of `ai_real`, or the fields read
+    /// from an imported scene are garbage.
+    #[test]
+    fn test_ai_real_layout() {
+        let real = if cfg!(feature = "double-precision") {
+            std::mem::size_of::<f64>()
+        } else {
+            std::mem::size_of::<f32>()
+        };
+        assert_eq!(std::mem::size_of::<aiVector3D>(), 3 * real);
+        assert_eq!(std::mem::size_of::<aiMatrix4x4>(), 16 * real);
+        if without_importer(c".obj") {
+            return;
+        }
+
+        let obj = b"v 0.123456789012345 2 3\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        let vertex = unsafe {
+            let scene = aiImportFileFromMemory(
+                obj.as_ptr() as *const _,
+                obj.len() as _,
+                0,
+                c"obj".as_ptr(),
+            );
+            assert!(!scene.is_null());
+            let mesh = &**(*scene).mMeshes;
+            let vertex = *mesh.mVertices;
+            aiReleaseImport(scene);
+            vertex
+        };
+        let tolerance = if cfg!(feature = "double-precision") {
+            1e-12
+        } else {
+            1e-6
+        };
+        assert!((vertex.x as f64 - 0.123456789012345).abs() < tolerance);
+        assert_eq!(vertex.y as f64, 2.0);
+    }
+
+    /// A node of a binary FBX 7.4 file: its name, encoded properties and children.
+    struct FbxNode(&'static str, Vec<Vec<u8>>, Vec<FbxNode>);
+
+    impl FbxNode {
+        fn write(&self, out: &mut Vec<u8>) {
+            let start = out.len();
+            // The end offset is absolute, it is filled in once the children are written.
+            out.extend([0; 4]);
+            out.extend((self.1.len() as u32).to_le_bytes());
+            out.extend((self.1.iter().map(Vec::len).sum::<usize>() as u32).to_le_bytes());
+            out.push(self.0.len() as u8);
+            out.extend(self.0.as_bytes());
+            for property in &self.1 {
+                out.extend(property);
+            }
+            if !self.2.is_empty() {
+                for child in &self.2 {
+                    child.write(out);
+                }
+                out.extend([0; 13]);
+            }
+            let end = out.len() as u32;
+            out[start..start + 4].copy_from_slice(&end.to_le_bytes());
+        }
+    }
+
+    fn fbx_int(value: i32) -> Vec<u8> {
+        [&[b'I'][..], &value.to_le_bytes()].concat()
+    }
+
+    fn fbx_id(value: i64) -> Vec<u8> {
+        [&[b'L'][..], &value.to_le_bytes()].concat()
+    }
+
+    fn fbx_string(value: &[u8]) -> Vec<u8> {
+        [&[b'S'][..], &(value.len() as u32).to_le_bytes(), value].concat()
+    }
+
+    /// An array property compressed with zlib, as exporters write large arrays.
+    fn fbx_compressed_array(kind: u8, count: usize, data: &[u8]) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        let compressed = encoder.finish().unwrap();
+        [
+            &[kind][..],
+            &(count as u32).to_le_bytes(),
+            &1u32.to_le_bytes(),
+            &(compressed.len() as u32).to_le_bytes(),
+            &compressed,
+        ]
+        .concat()
+    }
+
+    /// Imports a grid of quads from a binary FBX file with compressed vertex and index arrays, the
+    /// inflating of which the `zlib-ng` feature speeds up.
+    #[test]
+    fn test_compressed_fbx_import() {
+        if without_importer(c".fbx") {
+            return;
+        }
+        const SIZE: i32 = 300;
+        let vertices: Vec<u8> = (0..=SIZE)
+            .flat_map(|y| (0..=SIZE).map(move |x| [x as f64, y as f64, 0.0]))
+            .flatten()
+            .flat_map(f64::to_le_bytes)
+            .collect();
+        let indices: Vec<u8> = (0..SIZE)
+            .flat_map(|y| (0..SIZE).map(move |x| y * (SIZE + 1) + x))
+            .flat_map(|i| [i, i + 1, i + SIZE + 2, -(i + SIZE + 1) - 1])
+            .flat_map(i32::to_le_bytes)
+            .collect();
+
+        let nodes = [
+            FbxNode(
+                "FBXHeaderExtension",
+                vec![],
+                vec![FbxNode("FBXVersion", vec![fbx_int(7400)], vec![])],
+            ),
+            FbxNode(
+                "Objects",
+                vec![],
+                vec![
+                    FbxNode(
+                        "Geometry",
+                        vec![
+                            fbx_id(1),
+                            fbx_string(b"Grid\0\x01Geometry"),
+                            fbx_string(b"Mesh"),
+                        ],
+                        vec![
+                            FbxNode(
+                                "Vertices",
+                                vec![fbx_compressed_array(b'd', vertices.len() / 8, &vertices)],
+                                vec![],
+                            ),
+                            FbxNode(
+                                "PolygonVertexIndex",
+                                vec![fbx_compressed_array(b'i', indices.len() / 4, &indices)],
+                                vec![],
+                            ),
+                        ],
+                    ),
+                    FbxNode(
+                        "Model",
+                        vec![
+                            fbx_id(2),
+                            fbx_string(b"Grid\0\x01Model"),
+                            fbx_string(b"Mesh"),
+                        ],
+                        vec![FbxNode("Version", vec![fbx_int(232)], vec![])],
+                    ),
+                ],
+            ),
+            FbxNode(
+                "Connections",
+                vec![],
+                vec![
+                    FbxNode("C", vec![fbx_string(b"OO"), fbx_id(1), fbx_id(2)], vec![]),
+                    FbxNode("C", vec![fbx_string(b"OO"), fbx_id(2), fbx_id(0)], vec![]),
+                ],
+            ),
+        ];
+        let mut fbx = b"Kaydara FBX Binary  \0\x1a\0".to_vec();
+        fbx.extend(7400u32.to_le_bytes());
+        for node in &nodes {
+            node.write(&mut fbx);
+        }
+        fbx.extend([0; 13]);
+
+        let (num_vertices, num_faces) = unsafe {
+            let scene = aiImportFileFromMemory(
+                fbx.as_ptr() as *const _,
+                fbx.len() as _,
+                0,
+                c"fbx".as_ptr(),
+            );
+            assert!(!scene.is_null());
+            assert_eq!((*scene).mNumMeshes, 1);
+            let mesh = &**(*scene).mMeshes;
+            let counts = (mesh.mNumVertices, mesh.mNumFaces);
+            aiReleaseImport(scene);
+            counts
+        };
+        assert_eq!(num_faces, (SIZE * SIZE) as u32);
+        assert_eq!(num_vertices, 4 * num_faces);
+    }
+
+    /// Exports an imported cube through the export C API of `cexport.h`, to OBJ unless the export-*
+    /// features left its exporter out.
+    #[test]
+    #[cfg(not(assimp_no_export))]
+    fn test_export_scene() {
+        if without_importer(c".obj") {
+            return;
+        }
+        let obj = b"v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nv 0 0 1\nv 1 0 1\nv 1 1 1\nv 0 1 1\n\
+            f 1 4 3 2\nf 5 6 7 8\nf 1 2 6 5\nf 2 3 7 6\nf 3 4 8 7\nf 4 1 5 8\n";
+        unsafe {
+            let formats: Vec<std::ffi::CString> = (0..aiGetExportFormatCount())
+                .map(|index| {
+                    let desc = aiGetExportFormatDescription(index);
+                    assert!(!desc.is_null());
+                    let id = std::ffi::CStr::from_ptr((*desc).id).to_owned();
+                    aiReleaseExportFormatDescription(desc);
+                    id
+                })
+                .collect();
+            let format = formats
+                .iter()
+                .find(|id| id.to_bytes() == b"obj")
+                .or(formats.first())
+                .expect("assimp was built without exporters");
+
+            let scene = aiImportFileFromMemory(
+                obj.as_ptr() as *const _,
+                obj.len() as _,
+                0,
+                c"obj".as_ptr(),
+            );
+            assert!(!scene.is_null());
+            let mut copy = std::ptr::null_mut();
+            aiCopyScene(scene, &mut copy);
+            assert!(!copy.is_null());
+            aiReleaseImport(scene);
+
+            let blob = aiExportSceneToBlob(copy, format.as_ptr(), 0);
+            assert!(!blob.is_null(), "exporting to {:?} failed", format);
+            assert!((*blob).size > 0);
+            assert!(!(*blob).data.is_null());
+            aiReleaseExportBlob(blob);
+            aiFreeScene(copy);
+        }
+    }
+
+    /// The file of the in-memory file system of `test_custom_file_io`, in `aiFile::UserData`.
+    struct MemoryFile {
+        data: &'static [u8],
+        position: usize,
+    }
+
+    const MEMORY_OBJ: &[u8] = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+
+    unsafe fn memory_file<'a>(file: *mut aiFile) -> &'a mut MemoryFile {
+        &mut *((*file).UserData as *mut MemoryFile)
+    }
+
+    unsafe extern "C" fn memory_open(
+        _io: *mut aiFileIO,
+        path: *const std::os::raw::c_char,
+        _mode: *const std::os::raw::c_char,
+    ) -> *mut aiFile {
+        if std::ffi::CStr::from_ptr(path).to_bytes() != b"memory/triangle.obj" {
+            return std::ptr::null_mut();
+        }
+        let data = Box::into_raw(Box::new(MemoryFile {
+            data: MEMORY_OBJ,
+            position: 0,
+        }));
+        Box::into_raw(Box::new(aiFile {
+            ReadProc: Some(memory_read),
+            WriteProc: Some(memory_write),
+            TellProc: Some(memory_tell),
+            FileSizeProc: Some(memory_size),
+            SeekProc: Some(memory_seek),
+            FlushProc: Some(memory_flush),
+            UserData: data as aiUserData,
+        }))
+    }
+
+    unsafe extern "C" fn memory_close(_io: *mut aiFileIO, file: *mut aiFile) {
+        drop(Box::from_raw((*file).UserData as *mut MemoryFile));
+        drop(Box::from_raw(file));
+    }
+
+    unsafe extern "C" fn memory_read(
+        file: *mut aiFile,
+        buffer: *mut std::os::raw::c_char,
+        size: usize,
+        count: usize,
+    ) -> usize {
+        let file = memory_file(file);
+        let rest = &file.data[file.position..];
+        let count = count.min(rest.len() / size.max(1));
+        std::ptr::copy_nonoverlapping(rest.as_ptr(), buffer as *mut u8, count * size);
+        file.position += count * size;
+        count
+    }
+
+    unsafe extern "C" fn memory_write(
+        _file: *mut aiFile,
+        _buffer: *const std::os::raw::c_char,
+        _size: usize,
+        _count: usize,
+    ) -> usize {
+        0
+    }
+
+    unsafe extern "C" fn memory_tell(file: *mut aiFile) -> usize {
+        memory_file(file).position
+    }
+
+    unsafe extern "C" fn memory_size(file: *mut aiFile) -> usize {
+        memory_file(file).data.len()
+    }
+
+    unsafe extern "C" fn memory_seek(
+        file: *mut aiFile,
+        offset: usize,
+        origin: aiOrigin,
+    ) -> aiReturn {
+        let file = memory_file(file);
+        let position = match origin {
+            aiOrigin_aiOrigin_SET => Some(offset),
+            aiOrigin_aiOrigin_CUR => file.position.checked_add(offset),
+            aiOrigin_aiOrigin_END => file.data.len().checked_sub(offset),
+            _ => None,
+        };
+        match position.filter(|&position| position <= file.data.len()) {
+            Some(position) => {
+                file.position = position;
+                aiReturn_aiReturn_SUCCESS
+            }
+            None => aiReturn_aiReturn_FAILURE,
+        }
+    }
+
+    unsafe extern "C" fn memory_flush(_file: *mut aiFile) {}
+
+    /// Imports an OBJ from an in-memory file system through the callbacks of `cfileio.h`, which are
+    /// declared with the C calling convention the functions above have.
+    #[test]
+    fn test_custom_file_io() {
+        if without_importer(c".obj") {
+            return;
+        }
+        let open: aiFileOpenProc = Some(memory_open);
+        let close: aiFileCloseProc = Some(memory_close);
+        let mut io = aiFileIO {
+            OpenProc: open,
+            CloseProc: close,
+            UserData: std::ptr::null_mut(),
+        };
+        let import: unsafe extern "C" fn(
+            *const std::os::raw::c_char,
+            std::os::raw::c_uint,
+            *mut aiFileIO,
+        ) -> *const aiScene = aiImportFileEx;
+
+        unsafe {
+            let missing = import(c"memory/missing.obj".as_ptr(), 0, &mut io);
+            assert!(missing.is_null());
+
+            let scene = import(c"memory/triangle.obj".as_ptr(), 0, &mut io);
+            assert!(
+                !scene.is_null(),
+                "{:?}",
+                std::ffi::CStr::from_ptr(aiGetErrorString())
+            );
+            assert_eq!((*scene).mNumMeshes, 1);
+            assert_eq!((**(*scene).mMeshes).mNumFaces, 1);
+            aiReleaseImport(scene);
+
+            let scene = aiImportFileExWithProperties(
+                c"memory/triangle.obj".as_ptr(),
+                0,
+                &mut io,
+                std::ptr::null(),
+            );
+            assert!(!scene.is_null());
+            aiReleaseImport(scene);
+        }
+    }
+
+    /// The description of the OBJ importer, as tools show which importer loads a file.
+    #[test]
+    fn test_importer_desc() {
+        if without_importer(c".obj") {
+            return;
+        }
+        let desc = unsafe { aiGetImporterDesc(c"obj".as_ptr()) };
+        assert!(!desc.is_null());
+        let desc = unsafe { &*desc };
+        let extensions = unsafe { std::ffi::CStr::from_ptr(desc.mFileExtensions) };
+        assert!(
+            extensions
+                .to_string_lossy()
+                .split(' ')
+                .any(|ext| ext == "obj"),
+            "{:?}",
+            extensions
+        );
+        assert_ne!(
+            desc.mFlags
+                & aiImporterFlags_aiImporterFlags_SupportTextFlavour as std::os::raw::c_uint,
+            0
+        );
+        assert!(unsafe { aiGetImporterDesc(c"no-such-format".as_ptr()) }.is_null());
+    }
+
+    /// `AI_CONFIG_PP_SBP_REMOVE` drops the lines of an OBJ sorted by `aiProcess_SortByPType`.
+    #[test]
+    fn test_config_keys() {
+        assert_eq!(
+            config_keys::AI_CONFIG_PP_SBP_REMOVE.to_bytes(),
+            b"PP_SBP_REMOVE"
+        );
+        if without_importer(c".obj") {
+            return;
+        }
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\nl 1 2\n";
+        let primitive_types = unsafe {
+            let store = aiCreatePropertyStore();
+            aiSetImportPropertyInteger(
+                store,
+                config_keys::AI_CONFIG_PP_SBP_REMOVE.as_ptr(),
+                (aiPrimitiveType_aiPrimitiveType_POINT | aiPrimitiveType_aiPrimitiveType_LINE) as _,
+            );
+            let scene = aiImportFileFromMemoryWithProperties(
+                obj.as_ptr() as *const _,
+                obj.len() as _,
+                aiPostProcessSteps_aiProcess_SortByPType as _,
+                c"obj".as_ptr(),
+                store,
+            );
+            aiReleasePropertyStore(store);
+            assert!(!scene.is_null());
+            let meshes = std::slice::from_raw_parts((*scene).mMeshes, (*scene).mNumMeshes as _);
+            let types: Vec<u32> = meshes.iter().map(|mesh| (**mesh).mPrimitiveTypes).collect();
+            aiReleaseImport(scene);
+            types
+        };
+        assert_eq!(
+            primitive_types,
+            [aiPrimitiveType_aiPrimitiveType_TRIANGLE as std::os::raw::c_uint]
+        );
+    }
+
+    /// Reads the metallic-roughness material of a glTF through the generated material keys, the
+    /// core ones and the glTF aliases of `pbrmaterial.h`.
+    #[test]
+    fn test_gltf_material_keys() {
+        let gltf = br#"{
+            "asset": {"version": "2.0"},
+            "scene": 0,
+            "scenes": [{"nodes": [0]}],
+            "nodes": [{"mesh": 0}],
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "material": 0}]}],
+            "materials": [{
+                "pbrMetallicRoughness": {
+                    "baseColorFactor": [0.25, 0.5, 0.75, 1.0],
+                    "metallicFactor": 0.5
+                }
+            }],
+            "buffers": [{
+                "byteLength": 36,
+                "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAA"
+            }],
+            "bufferViews": [{"buffer": 0, "byteLength": 36}],
+            "accessors": [{
+                "bufferView": 0,
+                "componentType": 5126,
+                "count": 3,
+                "type": "VEC3",
+                "min": [0, 0, 0],
+                "max": [1, 1, 0]
+            }]
+        }"#;
+        let base_color = material_keys::gltf::AI_MATKEY_GLTF_PBRMETALLICROUGHNESS_BASE_COLOR_FACTOR;
+        assert_eq!(base_color, material_keys::AI_MATKEY_BASE_COLOR);
+        assert_eq!(base_color.key.to_bytes(), b"$clr.base");
+        assert_eq!(
+            material_keys::AI_MATKEY_BASE_COLOR_TEXTURE,
+            TextureSlot {
+                texture_type: aiTextureType_aiTextureType_BASE_COLOR as _,
+                index: 0,
+            }
+        );
+        if without_importer(c".gltf") {
+            return;
+        }
+        let (color, metallic) = unsafe {
+            let scene = aiImportFileFromMemory(
+                gltf.as_ptr() as *const _,
+                gltf.len() as _,
+                0,
+                c"gltf".as_ptr(),
+            );
+            assert!(!scene.is_null());
+            assert_eq!((*scene).mNumMaterials, 1);
+            let material = *(*scene).mMaterials;
+            let mut color = std::mem::zeroed::<aiColor4D>();
+            assert_eq!(
+                aiGetMaterialColor(
+                    material,
+                    base_color.key.as_ptr(),
+                    base_color.texture_type,
+                    base_color.index,
+                    &mut color,
+                ),
+                aiReturn_aiReturn_SUCCESS
+            );
+            let key = material_keys::gltf::AI_MATKEY_GLTF_PBRMETALLICROUGHNESS_METALLIC_FACTOR;
+            let mut metallic = std::mem::zeroed::<ai_real>();
+            assert_eq!(
+                aiGetMaterialFloatArray(
+                    material,
+                    key.key.as_ptr(),
+                    key.texture_type,
+                    key.index,
+                    &mut metallic,
+                    std::ptr::null_mut(),
+                ),
+                aiReturn_aiReturn_SUCCESS
+            );
+            aiReleaseImport(scene);
+            (color, metallic)
+        };
+        assert_eq!(
+            [color.r, color.g, color.b, color.a].map(|value| value as f64),
+            [0.25, 0.5, 0.75, 1.0]
+        );
+        assert_eq!(metallic as f64, 0.5);
+    }
+
+    /// Imports a triangle of a glTF compressed with KHR_draco_mesh_compression, whose meshes are
+    /// only decoded by an assimp built with draco. The buffer is a draco mesh, with uncompressed
+    /// indices and positions.
+    #[test]
+    #[cfg(feature = "draco")]
+    fn test_draco_gltf_import() {
+        if without_importer(c".gltf") {
+            return;
+        }
+        let gltf = br#"{
+            "asset": {"version": "2.0"},
+            "extensionsUsed": ["KHR_draco_mesh_compression"],
+            "extensionsRequired": ["KHR_draco_mesh_compression"],
+            "scene": 0,
+            "scenes": [{"nodes": [0]}],
+            "nodes": [{"mesh": 0}],
+            "meshes": [{"primitives": [{
+                "attributes": {"POSITION": 0},
+                "indices": 1,
+                "extensions": {"KHR_draco_mesh_compression": {
+                    "bufferView": 0,
+                    "attributes": {"POSITION": 0}
+                }}
+            }]}],
+            "buffers": [{
+                "byteLength": 61,
+                "uri": "data:application/octet-stream;base64,RFJBQ08CAgEAAAABAwEAAQIBAQAJAwAAAAAAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAA=="
+            }],
+            "bufferViews": [{"buffer": 0, "byteLength": 61}],
+            "accessors": [
+                {"componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0]},
+                {"componentType": 5123, "count": 3, "type": "SCALAR"}
+            ]
+        }"#;
+        let (vertices, indices) = unsafe {
+            let scene = aiImportFileFromMemory(
+                gltf.as_ptr() as *const _,
+                gltf.len() as _,
+                0,
+                c"gltf".as_ptr(),
+            );
+            assert!(!scene.is_null());
+            assert_eq!((*scene).mNumMeshes, 1);
+            let mesh = &**(*scene).mMeshes;
+            assert_eq!(mesh.mNumFaces, 1);
+            let vertices: Vec<[f64; 3]> =
+                std::slice::from_raw_parts(mesh.mVertices, mesh.mNumVertices as usize)
+                    .iter()
+                    .map(|v| [v.x as f64, v.y as f64, v.z as f64])
+                    .collect();
+            let face = &*mesh.mFaces;
+            let indices =
+                std::slice::from_raw_parts(face.mIndices, face.mNumIndices as usize).to_vec();
+            aiReleaseImport(scene);
+            (vertices, indices)
+        };
+        assert_eq!(
+            vertices,
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+        assert_eq!(indices, [0, 1, 2]);
+    }
+
+    /// Without `no-export` the library has exporters. With it the export functions aren't in the
+    /// library at all and fail to link, so only the importers of the import-only build are counted.
+    #[test]
+    fn test_no_export() {
+        #[cfg(not(assimp_no_export))]
+        assert_ne!(unsafe { aiGetExportFormatCount() }, 0);
+        #[cfg(assimp_no_export)]
+        assert_ne!(unsafe { aiGetImportFormatCount() }, 0);
+    }
+
+    #[test]
+    fn test_assimp_license() {
+        assert!(assimp_license().contains("assimp team"));
+    }
 }
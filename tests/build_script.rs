@@ -0,0 +1,66 @@
+//! Unit tests of the build script, which is compiled into this test target as-is.
+//! `main` is never called here, so most of it is dead code.
+#![allow(dead_code)]
+
+include!("../build.rs");
+
+mod package_archive {
+    include!("../bin/package/archive.rs");
+}
+
+/// Creates a fake assimp install and packages it to `archive_name` the way
+/// `cargo run --bin package` does, returning the archive.
+fn package_fake_install(dir: &Path, archive_name: &str) -> PathBuf {
+    let _ = fs::remove_dir_all(dir);
+    let install_dir = dir.join("install");
+    fs::create_dir_all(install_dir.join("include").join("assimp")).unwrap();
+    fs::write(
+        install_dir.join("include").join("assimp").join("version.h"),
+        "",
+    )
+    .unwrap();
+    let target = TargetInfo::host();
+    let patterns = assimp_library(&target, false).file_patterns(&target);
+    for (subdir, pattern) in &patterns {
+        fs::create_dir_all(install_dir.join(subdir)).unwrap();
+        fs::write(install_dir.join(subdir).join(pattern.replace('*', "")), "").unwrap();
+    }
+    let license = dir.join("LICENSE");
+    fs::write(&license, "BSD-3-Clause").unwrap();
+
+    let archive = dir.join(archive_name);
+    let with_bin = patterns.iter().any(|(subdir, _)| *subdir == "bin");
+    package_archive::write_package(&install_dir, &license, &archive, with_bin).unwrap();
+    archive
+}
+
+/// Checks that the extraction of `link_from_package()` accepts `archive`.
+fn assert_unpacks(dir: &Path, archive: &Path) {
+    let extracted = dir.join("extracted");
+    unpack_package(archive, &extracted).unwrap();
+    validate_package(&TargetInfo::host(), &extracted).unwrap();
+    assert!(extracted
+        .join("include")
+        .join("assimp")
+        .join("version.h")
+        .is_file());
+    assert_eq!(
+        fs::read_to_string(extracted.join("LICENSE")).unwrap(),
+        "BSD-3-Clause"
+    );
+}
+
+#[test]
+fn packaged_archives_round_trip() {
+    let dir = env::temp_dir().join(format!("russimp-sys-package-{}", std::process::id()));
+    let archive = package_fake_install(&dir, "package.tar.gz");
+    assert_unpacks(&dir, &archive);
+}
+
+#[cfg(feature = "prebuilt")]
+#[test]
+fn zstd_packaged_archives_round_trip() {
+    let dir = env::temp_dir().join(format!("russimp-sys-package-zstd-{}", std::process::id()));
+    let archive = package_fake_install(&dir, "package.tar.zst");
+    assert_unpacks(&dir, &archive);
+}
@@ -0,0 +1,13 @@
+//! Links the static assimp of `static-link` into the `cdylib` of `tests/cdylib`, which fails with
+//! relocations "recompile with -fPIC" unless assimp and its zlib are position independent code.
+
+mod common;
+
+#[test]
+#[cfg_attr(
+    not(feature = "static-link"),
+    ignore = "links the static assimp of `static-link`"
+)]
+fn static_assimp_links_into_a_cdylib() {
+    common::run_fixture("cdylib", "build", &[]);
+}
@@ -0,0 +1,11 @@
+use russimp_sys::{aiImportFile, aiReleaseImport};
+use std::os::raw::c_char;
+
+/// Pulls the importers of assimp into the library, with their relocations.
+#[no_mangle]
+pub unsafe extern "C" fn russimp_cdylib_import(path: *const c_char) -> bool {
+    let scene = aiImportFile(path, 0);
+    let imported = !scene.is_null();
+    aiReleaseImport(scene);
+    imported
+}
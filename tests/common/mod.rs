@@ -0,0 +1,27 @@
+//! Runs the fixture crates in `tests/`, which depend on russimp-sys by path like a dependent does.
+
+use std::{env, path::Path, process::Command};
+
+/// Runs `cargo <command> <args>` on the fixture crate in `tests/<name>`, asserting it succeeds.
+pub fn run_fixture(name: &str, command: &str, args: &[&str]) {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(name);
+    // The target directory of this build is locked while the tests run.
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let status = Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+        .arg(command)
+        .arg("--manifest-path")
+        .arg(fixture.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .args(args)
+        .status()
+        .expect("Unable to run cargo");
+    assert!(
+        status.success(),
+        "cargo {} of tests/{} failed",
+        command,
+        name
+    );
+}
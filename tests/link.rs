@@ -0,0 +1,51 @@
+//! Links russimp-sys like a dependent crate does and calls into the importers, the post-processing
+//! steps and the exporters, which use the C++ runtime and zlib: a static assimp linked in the wrong
+//! order fails to link this binary with undefined references.
+
+use russimp_sys::*;
+
+const QUAD: &str =
+    "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\nf 1/1 2/2 3/3 4/4\n";
+
+#[test]
+fn imports_processes_and_exports_a_scene() {
+    unsafe {
+        // The OBJ importer may be left out by the `format-*` features, the calls are linked anyway.
+        if aiIsExtensionSupported(c".obj".as_ptr()) == 0 {
+            return;
+        }
+        let scene = aiImportFileFromMemory(
+            QUAD.as_ptr() as *const _,
+            QUAD.len() as _,
+            aiPostProcessSteps_aiProcess_Triangulate
+                | aiPostProcessSteps_aiProcess_JoinIdenticalVertices
+                | aiPostProcessSteps_aiProcess_CalcTangentSpace,
+            c"obj".as_ptr(),
+        );
+        assert!(!scene.is_null());
+        assert_eq!((**(*scene).mMeshes).mNumFaces, 2);
+
+        // Without its exporters, assimp has none of these symbols.
+        #[cfg(not(feature = "no-export"))]
+        {
+            // The OBJ exporter may be left out by the `export-*` features.
+            if has_exporter(b"obj") {
+                let blob = aiExportSceneToBlob(scene, c"obj".as_ptr(), 0);
+                assert!(!blob.is_null());
+                assert!((*blob).size > 0);
+                aiReleaseExportBlob(blob);
+            }
+        }
+        aiReleaseImport(scene);
+    }
+}
+
+#[cfg(not(feature = "no-export"))]
+unsafe fn has_exporter(id: &[u8]) -> bool {
+    (0..aiGetExportFormatCount()).any(|index| {
+        let desc = aiGetExportFormatDescription(index);
+        let found = std::ffi::CStr::from_ptr((*desc).id).to_bytes() == id;
+        aiReleaseExportFormatDescription(desc);
+        found
+    })
+}
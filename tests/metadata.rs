@@ -0,0 +1,22 @@
+//! Tests the `tests/metadata` crate, whose build script compiles a C shim against the headers
+//! russimp-sys exports in `DEP_ASSIMP_INCLUDE`, with the features this crate is tested with.
+
+mod common;
+
+/// The features selecting how the fixture gets assimp.
+const FORWARDED_FEATURES: &[(&str, bool)] = &[
+    ("build-assimp", cfg!(feature = "build-assimp")),
+    ("prebuilt", cfg!(feature = "prebuilt")),
+    ("static-link", cfg!(feature = "static-link")),
+    ("system", cfg!(feature = "system")),
+];
+
+#[test]
+fn dependents_compile_against_the_exported_metadata() {
+    let features: Vec<String> = FORWARDED_FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(feature, _)| format!("russimp-sys/{}", feature))
+        .collect();
+    common::run_fixture("metadata", "test", &["--features", &features.join(",")]);
+}
@@ -0,0 +1,27 @@
+use std::{env, path::PathBuf};
+
+fn main() {
+    let var = |name: &str| {
+        env::var_os(name).unwrap_or_else(|| panic!("russimp-sys exported no {}", name))
+    };
+    let include = PathBuf::from(var("DEP_ASSIMP_INCLUDE"));
+    let root = PathBuf::from(var("DEP_ASSIMP_ROOT"));
+    let lib = var("DEP_ASSIMP_LIB");
+    assert!(
+        include.join("assimp").join("version.h").is_file(),
+        "DEP_ASSIMP_INCLUDE={} has no assimp/version.h",
+        include.display()
+    );
+    assert!(
+        root.is_dir(),
+        "DEP_ASSIMP_ROOT={} is no directory",
+        root.display()
+    );
+    assert!(!lib.is_empty(), "DEP_ASSIMP_LIB is empty");
+
+    cc::Build::new()
+        .file("src/shim.c")
+        .include(&include)
+        .compile("russimp_sys_metadata_shim");
+    println!("cargo:rerun-if-changed=src/shim.c");
+}
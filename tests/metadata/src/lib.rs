@@ -0,0 +1,10 @@
+extern "C" {
+    /// `aiGetVersionMajor()`, called from the C shim compiled against `DEP_ASSIMP_INCLUDE`.
+    fn russimp_sys_metadata_version_major() -> std::os::raw::c_uint;
+}
+
+#[test]
+fn shim_compiles_against_the_exported_headers() {
+    let major = unsafe { russimp_sys_metadata_version_major() };
+    assert_eq!(major, unsafe { russimp_sys::aiGetVersionMajor() });
+}
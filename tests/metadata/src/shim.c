@@ -0,0 +1,5 @@
+#include <assimp/version.h>
+
+unsigned int russimp_sys_metadata_version_major(void) {
+    return aiGetVersionMajor();
+}
@@ -0,0 +1,48 @@
+//! Runs this test binary again without `LD_LIBRARY_PATH` and `DYLD_LIBRARY_PATH`, which cargo sets
+//! for it: a shared assimp has to be found through the rpaths the build script links it with.
+#![cfg(unix)]
+
+use russimp_sys::{aiImportFileFromMemory, aiIsExtensionSupported, aiReleaseImport};
+use std::{env, process::Command};
+
+const TRIANGLE: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+
+#[test]
+fn imports_without_library_path_variables() {
+    if env::var_os("RUSSIMP_RPATH_TEST_CHILD").is_some() {
+        import_triangle();
+        return;
+    }
+
+    let output = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "imports_without_library_path_variables"])
+        .env("RUSSIMP_RPATH_TEST_CHILD", "1")
+        .env_remove("LD_LIBRARY_PATH")
+        .env_remove("DYLD_LIBRARY_PATH")
+        .env_remove("DYLD_FALLBACK_LIBRARY_PATH")
+        .output()
+        .expect("Unable to run the test binary");
+    assert!(
+        output.status.success(),
+        "the test binary failed without the library path variables: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn import_triangle() {
+    unsafe {
+        // The OBJ importer may be left out by the `format-*` features.
+        if aiIsExtensionSupported(c".obj".as_ptr()) == 0 {
+            return;
+        }
+        let scene = aiImportFileFromMemory(
+            TRIANGLE.as_ptr() as *const _,
+            TRIANGLE.len() as _,
+            0,
+            c"obj".as_ptr(),
+        );
+        assert!(!scene.is_null());
+        assert_eq!((*scene).mNumMeshes, 1);
+        aiReleaseImport(scene);
+    }
+}
@@ -0,0 +1,18 @@
+//! Runs the assimp command-line tool of `tools` from the path the build script generated, which
+//! only starts when it finds the shared assimp next to it or through its rpath.
+
+use std::process::Command;
+
+#[test]
+#[cfg_attr(
+    not(feature = "tools"),
+    ignore = "runs the command-line tool of `tools`"
+)]
+fn assimp_cli_runs() {
+    let tool = russimp_sys::built_info::assimp_cli_path().expect("`tools` built no assimp tool");
+    let output = Command::new(tool)
+        .arg("version")
+        .output()
+        .unwrap_or_else(|e| panic!("Unable to run {}: {}", tool.display(), e));
+    assert!(output.status.success(), "{:?}", output);
+}